@@ -11,4 +11,9 @@ fn main() {
             println!("cargo:rerun-if-changed=server.proto");
         }
     }
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "node")] {
+            napi_build::setup();
+        }
+    }
 }
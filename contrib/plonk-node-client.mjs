@@ -0,0 +1,18 @@
+import * as fs from 'fs';
+import { createRequire } from 'module';
+const require = createRequire(import.meta.url);
+const { Prover } = require(process.env.PLONKIT_NODE || './plonkit.node');
+
+const prover = new Prover(
+  process.env.CIRCUIT || 'circuit.r1cs',
+  process.env.SRS_MONOMIAL_FORM || 'setup_2^20.key',
+);
+
+// proving runs on a worker thread, the returned promise resolves to { proof, inputs }
+export async function prove(witness_fn) {
+  return await prover.prove(fs.readFileSync(witness_fn || 'witness.wtns'));
+}
+
+export function verify({ proof, inputs }) {
+  return prover.verify(proof, inputs);
+}
@@ -0,0 +1,96 @@
+#ifndef PLONKIT_H
+#define PLONKIT_H
+
+/* Generated with cbindgen from src/ffi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct PlonkitProver PlonkitProver;
+
+typedef struct PlonkitVerifier PlonkitVerifier;
+
+typedef struct {
+  uint8_t *data;
+  uintptr_t len;
+} PlonkitBuffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Loads a circuit (.r1cs or JSON) and prepares it for proving with the srs. `srs_lagrange` and
+// `proving_key` are optional and may be NULL. Returns NULL on failure.
+//
+// # Safety
+// Every non NULL pointer must point to the given number of readable bytes.
+PlonkitProver *plonkit_prover_new(const uint8_t *circuit,
+                                  uintptr_t circuit_len,
+                                  const uint8_t *srs_monomial,
+                                  uintptr_t srs_monomial_len,
+                                  const uint8_t *srs_lagrange,
+                                  uintptr_t srs_lagrange_len,
+                                  const uint8_t *proving_key,
+                                  uintptr_t proving_key_len,
+                                  char **error);
+
+// # Safety
+// `prover` must come from `plonkit_prover_new` and not be used afterwards. NULL is ignored.
+void plonkit_prover_free(PlonkitProver *prover);
+
+// Writes the verification key of the circuit to `vk`. Returns 0 on success, -1 on failure.
+//
+// # Safety
+// `prover` must come from `plonkit_prover_new`, `vk` must be writable.
+int plonkit_prover_verification_key(const PlonkitProver *prover,
+                                    PlonkitBuffer *vk,
+                                    char **error);
+
+// Proves a witness in the .wtns format and writes the proof, in the format of `plonkit prove --proof`,
+// to `proof`. Returns 0 on success, -1 on failure. A prover may be used from several threads at once.
+//
+// # Safety
+// `prover` must come from `plonkit_prover_new`, `witness` must point to `witness_len` readable bytes
+// and `proof` must be writable.
+int plonkit_prove(const PlonkitProver *prover,
+                  const uint8_t *witness,
+                  uintptr_t witness_len,
+                  PlonkitBuffer *proof,
+                  char **error);
+
+// Loads a verification key as written by `plonkit export-verification-key`. Returns NULL on failure.
+//
+// # Safety
+// `vk` must point to `vk_len` readable bytes.
+PlonkitVerifier *plonkit_verifier_new(const uint8_t *vk,
+                                      uintptr_t vk_len,
+                                      char **error);
+
+// # Safety
+// `verifier` must come from `plonkit_verifier_new` and not be used afterwards. NULL is ignored.
+void plonkit_verifier_free(PlonkitVerifier *verifier);
+
+// Returns 1 when the proof is valid, 0 when it is not and -1 when it cannot be checked.
+//
+// # Safety
+// `verifier` must come from `plonkit_verifier_new`, `proof` must point to `proof_len` readable bytes.
+int plonkit_verify(const PlonkitVerifier *verifier,
+                   const uint8_t *proof,
+                   uintptr_t proof_len,
+                   char **error);
+
+// # Safety
+// `buffer` must have been filled by the library and not be freed twice.
+void plonkit_buffer_free(PlonkitBuffer buffer);
+
+// # Safety
+// `error` must have been set by the library and not be freed twice. NULL is ignored.
+void plonkit_error_free(char *error);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* PLONKIT_H */
@@ -0,0 +1,169 @@
+// Entry points for embedding plonkit, they wire the circuit, the setup and the srs together the way the cli does.
+use bellman_ce::{
+    kate_commitment::{Crs, CrsForLagrangeForm, CrsForMonomialForm},
+    pairing::{bn256::Bn256, ff::PrimeField, Engine},
+    plonk::better_cs::{
+        cs::PlonkCsWidth4WithNextStepParams,
+        keys::{Proof, VerificationKey},
+    },
+};
+use itertools::Itertools;
+use std::sync::Arc;
+
+use crate::circom_circuit::{CircomCircuit, R1CS};
+use crate::encoding;
+use crate::error::{Error, Result};
+use crate::plonk::{self, SetupForProver};
+use crate::sym_file::SymbolTable;
+use crate::utils::repr_to_big;
+
+/// A proof together with its public inputs.
+pub struct ProofArtifacts<E: Engine> {
+    pub proof: Proof<E, PlonkCsWidth4WithNextStepParams>,
+    /// Public inputs as decimal strings, in the order of the circuit
+    pub public_inputs: Vec<String>,
+    /// Proof as the solidity verifier takes it, the same values as `proof.json`. `None` on curves other than bn256.
+    pub serialized_proof: Option<Vec<String>>,
+}
+
+/// Proves witnesses of one circuit, it holds the transpiled setup so that it is only computed once.
+pub struct Prover<E: Engine> {
+    circuit: CircomCircuit<E>,
+    setup: SetupForProver<E>,
+}
+
+pub struct ProverBuilder<E: Engine> {
+    r1cs: Option<R1CS<E>>,
+    wire_mapping: Option<Vec<usize>>,
+    symbols: Option<Arc<SymbolTable>>,
+    proving_key: Option<Vec<u8>>,
+    key_monomial_form: Option<Crs<E, CrsForMonomialForm>>,
+    key_lagrange_form: Option<Crs<E, CrsForLagrangeForm>>,
+}
+
+impl<E: Engine> ProverBuilder<E> {
+    /// The circuit with its wire map, as returned by `reader::load_r1cs`.
+    pub fn circuit(mut self, r1cs: R1CS<E>, wire_mapping: Option<Vec<usize>>) -> Self {
+        self.r1cs = Some(r1cs);
+        self.wire_mapping = wire_mapping;
+        self
+    }
+
+    /// Debug symbols, used to name the signals of unsatisfied constraints.
+    pub fn symbols(mut self, symbols: SymbolTable) -> Self {
+        self.symbols = Some(Arc::new(symbols));
+        self
+    }
+
+    /// A proving key exported from the same circuit, it skips the transpilation in `build`.
+    pub fn proving_key(mut self, proving_key: Vec<u8>) -> Self {
+        self.proving_key = Some(proving_key);
+        self
+    }
+
+    pub fn srs_monomial(mut self, key_monomial_form: Crs<E, CrsForMonomialForm>) -> Self {
+        self.key_monomial_form = Some(key_monomial_form);
+        self
+    }
+
+    /// Optional, proving is faster with the lagrange form of the srs.
+    pub fn srs_lagrange(mut self, key_lagrange_form: Crs<E, CrsForLagrangeForm>) -> Self {
+        self.key_lagrange_form = Some(key_lagrange_form);
+        self
+    }
+
+    pub fn build(self) -> Result<Prover<E>> {
+        let r1cs = self.r1cs.ok_or_else(|| Error::Format("the prover needs a circuit".to_owned()))?;
+        let key_monomial_form = self
+            .key_monomial_form
+            .ok_or_else(|| Error::Format("the prover needs a srs in monomial form".to_owned()))?;
+        let circuit = CircomCircuit {
+            r1cs,
+            witness: None,
+            wire_mapping: self.wire_mapping,
+            aux_offset: plonk::AUX_OFFSET,
+            symbols: self.symbols,
+        };
+        let setup = match self.proving_key {
+            Some(proving_key) => SetupForProver::load(proving_key.as_slice(), &circuit.r1cs, key_monomial_form, self.key_lagrange_form)?,
+            None => SetupForProver::prepare_setup_for_prover(circuit.clone(), key_monomial_form, self.key_lagrange_form)?,
+        };
+        Ok(Prover { circuit, setup })
+    }
+}
+
+impl<E: Engine> Prover<E> {
+    pub fn builder() -> ProverBuilder<E> {
+        ProverBuilder {
+            r1cs: None,
+            wire_mapping: None,
+            symbols: None,
+            proving_key: None,
+            key_monomial_form: None,
+            key_lagrange_form: None,
+        }
+    }
+
+    pub fn circuit(&self) -> &CircomCircuit<E> {
+        &self.circuit
+    }
+
+    pub fn verification_key(&self) -> Result<VerificationKey<E, PlonkCsWidth4WithNextStepParams>> {
+        self.setup.make_verification_key()
+    }
+
+    /// Checks the witness without proving, see `SetupForProver::validate_witness`.
+    pub fn validate(&self, witness: Vec<E::Fr>) -> Result<()> {
+        self.setup.validate_witness(self.with_witness(witness))
+    }
+
+    pub fn prove(&self, witness: Vec<E::Fr>) -> Result<ProofArtifacts<E>> {
+        let proof = self.setup.prove(self.with_witness(witness))?;
+        let public_inputs = proof.input_values.iter().map(|x| repr_to_big(x.into_repr())).collect_vec();
+        // the solidity encoding only exists for bn256
+        let serialized_proof = match encoding::convert_proof::<E, Bn256>(&proof) {
+            Ok(proof) => {
+                let (_, serialized_proof) = bellman_vk_codegen::serialize_proof(&proof);
+                Some(serialized_proof.iter().map(ToString::to_string).collect_vec())
+            }
+            Err(Error::FieldMismatch(_)) => None,
+            Err(err) => return Err(err),
+        };
+        Ok(ProofArtifacts {
+            proof,
+            public_inputs,
+            serialized_proof,
+        })
+    }
+
+    fn with_witness(&self, witness: Vec<E::Fr>) -> CircomCircuit<E> {
+        let mut circuit = self.circuit.clone();
+        circuit.set_witness(witness);
+        circuit
+    }
+}
+
+pub struct Verifier<E: Engine> {
+    vk: VerificationKey<E, PlonkCsWidth4WithNextStepParams>,
+}
+
+impl<E: Engine> Verifier<E> {
+    pub fn from_vk(vk: VerificationKey<E, PlonkCsWidth4WithNextStepParams>) -> Self {
+        Verifier { vk }
+    }
+
+    pub fn verification_key(&self) -> &VerificationKey<E, PlonkCsWidth4WithNextStepParams> {
+        &self.vk
+    }
+
+    pub fn verify(&self, proof: &Proof<E, PlonkCsWidth4WithNextStepParams>) -> Result<bool> {
+        plonk::verify(&self.vk, proof)
+    }
+}
+
+#[test]
+fn prover_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Prover<Bn256>>();
+    assert_send_sync::<Verifier<Bn256>>();
+}
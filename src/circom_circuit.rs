@@ -4,13 +4,18 @@ extern crate rand;
 
 use itertools::Itertools;
 use std::collections::BTreeMap;
+use std::fmt;
 use std::str;
+use std::sync::Arc;
 
 use bellman_ce::{
-    pairing::{ff::PrimeField, ff::ScalarEngine, Engine},
+    pairing::{ff::Field, ff::PrimeField, ff::PrimeFieldRepr, ff::ScalarEngine, Engine},
     Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable,
 };
+use sha2::{Digest, Sha256};
 
+use crate::error::{Error, Result};
+use crate::sym_file::SymbolTable;
 use crate::utils::repr_to_big;
 
 #[derive(Serialize, Deserialize)]
@@ -30,6 +35,19 @@ pub type Constraint<E> = (
     Vec<(usize, <E as ScalarEngine>::Fr)>,
 );
 
+/// Distinct wires referenced by a constraint, in ascending order.
+pub fn constraint_wires<E: Engine>(constraint: &Constraint<E>) -> Vec<usize> {
+    constraint
+        .0
+        .iter()
+        .chain(constraint.1.iter())
+        .chain(constraint.2.iter())
+        .map(|(wire, _)| *wire)
+        .sorted()
+        .dedup()
+        .collect_vec()
+}
+
 #[derive(Clone)]
 pub struct R1CS<E: Engine> {
     pub num_inputs: usize,
@@ -38,16 +56,105 @@ pub struct R1CS<E: Engine> {
     pub constraints: Vec<Constraint<E>>,
 }
 
+impl<E: Engine> R1CS<E> {
+    /// Digest of the constraint system, used to bind derived artifacts (e.g. proving keys) to their circuit.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for n in &[self.num_inputs, self.num_aux, self.num_variables, self.constraints.len()] {
+            hasher.update(&(*n as u64).to_le_bytes());
+        }
+        let mut buf = Vec::with_capacity(32);
+        for constraint in &self.constraints {
+            for lc in &[&constraint.0, &constraint.1, &constraint.2] {
+                hasher.update(&(lc.len() as u64).to_le_bytes());
+                for (index, coeff) in lc.iter() {
+                    buf.clear();
+                    coeff.into_repr().write_le(&mut buf).expect("write to vec");
+                    hasher.update(&(*index as u64).to_le_bytes());
+                    hasher.update(&buf);
+                }
+            }
+        }
+        let mut result = [0u8; 32];
+        result.copy_from_slice(&hasher.finalize());
+        result
+    }
+}
+
+/// An R1CS constraint `A * B = C` that does not hold for a witness.
+/// Field elements are kept as decimal strings.
+#[derive(Clone, Debug, Serialize)]
+pub struct UnsatisfiedConstraint {
+    pub index: usize,
+    pub a: String,
+    pub b: String,
+    pub ab: String,
+    pub c: String,
+    pub wires: Vec<usize>,
+    /// Signal names of `wires`, when debug symbols are loaded
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub signals: Vec<String>,
+}
+
+impl fmt::Display for UnsatisfiedConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraint {} is not satisfied: A = {}, B = {}, A*B = {}, C = {} (wires: {})",
+            self.index,
+            self.a,
+            self.b,
+            self.ab,
+            self.c,
+            self.wires
+                .iter()
+                .enumerate()
+                .map(|(i, wire)| match self.signals.get(i) {
+                    Some(signal) => format!("{} {}", wire, signal),
+                    None => wire.to_string(),
+                })
+                .join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnsatisfiedConstraint {}
+
+/// Outcome of evaluating a witness against every R1CS constraint.
+#[derive(Clone, Debug, Serialize)]
+pub struct WitnessReport {
+    pub witness_len: usize,
+    pub expected_witness_len: usize,
+    pub wire_0_is_one: bool,
+    pub num_constraints: usize,
+    pub unsatisfied_constraints: Vec<UnsatisfiedConstraint>,
+}
+
+impl WitnessReport {
+    pub fn is_valid(&self) -> bool {
+        self.witness_len == self.expected_witness_len && self.wire_0_is_one && self.unsatisfied_constraints.is_empty()
+    }
+}
+
 #[derive(Clone)]
 pub struct CircomCircuit<E: Engine> {
     pub r1cs: R1CS<E>,
     pub witness: Option<Vec<E::Fr>>,
     pub wire_mapping: Option<Vec<usize>>,
     pub aux_offset: usize,
-    // debug symbols
+    pub symbols: Option<Arc<SymbolTable>>,
 }
 
 impl<'a, E: Engine> CircomCircuit<E> {
+    /// Sets the witness, indexed by signal label as written by circom. A witness with one value per wire is
+    /// already in wire order, the wire map is dropped for it.
+    pub fn set_witness(&mut self, witness: Vec<E::Fr>) {
+        if witness.len() == self.r1cs.num_variables {
+            self.wire_mapping = None;
+        }
+        self.witness = Some(witness);
+    }
+
     pub fn get_public_inputs(&self) -> Option<Vec<E::Fr>> {
         match &self.witness {
             None => None,
@@ -66,6 +173,106 @@ impl<'a, E: Engine> CircomCircuit<E> {
         };
         serde_json::to_string_pretty(&inputs).unwrap()
     }
+
+    /// Signal names of the wires, empty when no debug symbols are loaded.
+    pub fn signal_names(&self, wires: &[usize]) -> Vec<String> {
+        match &self.symbols {
+            None => Vec::new(),
+            Some(symbols) => wires
+                .iter()
+                .map(|wire| {
+                    symbols
+                        .wire_name(*wire)
+                        .map_or_else(|| format!("<wire {}>", wire), |name| name.to_owned())
+                })
+                .collect_vec(),
+        }
+    }
+
+    // the wire map and the witness come from user files, a missing entry must not panic
+    fn wire_value(&self, witness: &[E::Fr], wire: usize) -> Result<E::Fr> {
+        let index = match &self.wire_mapping {
+            None => wire,
+            Some(m) => m
+                .get(wire)
+                .copied()
+                .ok_or_else(|| Error::InvalidWitness(format!("wire {} is missing from the wire map", wire)))?,
+        };
+        witness
+            .get(index)
+            .cloned()
+            .ok_or_else(|| Error::InvalidWitness(format!("no value for wire {} at index {}", wire, index)))
+    }
+
+    fn evaluate_lc(&self, witness: &[E::Fr], lc: &[(usize, E::Fr)]) -> Result<E::Fr> {
+        let mut result = E::Fr::zero();
+        for (wire, coeff) in lc {
+            let mut term = self.wire_value(witness, *wire)?;
+            term.mul_assign(coeff);
+            result.add_assign(&term);
+        }
+        Ok(result)
+    }
+
+    /// Evaluates the constraint `index` against the witness, returns `None` if it holds or there is no witness.
+    pub fn check_constraint(&self, index: usize) -> Result<Option<UnsatisfiedConstraint>> {
+        let witness = match &self.witness {
+            Some(witness) => witness,
+            None => return Ok(None),
+        };
+        let constraint = &self.r1cs.constraints[index];
+        let a = self.evaluate_lc(witness, &constraint.0)?;
+        let b = self.evaluate_lc(witness, &constraint.1)?;
+        let c = self.evaluate_lc(witness, &constraint.2)?;
+        let mut ab = a;
+        ab.mul_assign(&b);
+        if ab == c {
+            return Ok(None);
+        }
+        let wires = constraint_wires::<E>(constraint);
+        Ok(Some(UnsatisfiedConstraint {
+            index,
+            a: repr_to_big(a.into_repr()),
+            b: repr_to_big(b.into_repr()),
+            ab: repr_to_big(ab.into_repr()),
+            c: repr_to_big(c.into_repr()),
+            signals: self.signal_names(&wires),
+            wires,
+        }))
+    }
+
+    /// Finds the first R1CS constraint the witness does not satisfy.
+    pub fn find_unsatisfied_constraint(&self) -> Result<Option<UnsatisfiedConstraint>> {
+        for i in 0..self.r1cs.constraints.len() {
+            if let Some(constraint) = self.check_constraint(i)? {
+                return Ok(Some(constraint));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks the witness shape and evaluates every constraint, collecting all failures. Constraints are only
+    /// evaluated for a witness of the expected length.
+    pub fn check_witness(&self) -> Result<WitnessReport> {
+        let witness: &[E::Fr] = self.witness.as_ref().map(Vec::as_slice).unwrap_or(&[]);
+        let expected_witness_len = match &self.wire_mapping {
+            None => self.r1cs.num_variables,
+            Some(m) => m.iter().max().map_or(0, |i| i + 1),
+        };
+        let mut report = WitnessReport {
+            witness_len: witness.len(),
+            expected_witness_len,
+            wire_0_is_one: !witness.is_empty() && self.wire_value(witness, 0)? == E::Fr::one(),
+            num_constraints: self.r1cs.constraints.len(),
+            unsatisfied_constraints: Vec::new(),
+        };
+        if witness.len() == expected_witness_len {
+            for i in 0..self.r1cs.constraints.len() {
+                report.unsatisfied_constraints.extend(self.check_constraint(i)?);
+            }
+        }
+        Ok(report)
+    }
 }
 
 /// Our demo circuit implements this `Circuit` trait which
@@ -73,7 +280,7 @@ impl<'a, E: Engine> CircomCircuit<E> {
 /// synthesize the constraint system.
 impl<'a, E: Engine> Circuit<E> for CircomCircuit<E> {
     //noinspection RsBorrowChecker
-    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> std::result::Result<(), SynthesisError> {
         let witness = &self.witness;
         let wire_mapping = &self.wire_mapping;
         for i in 1..self.r1cs.num_inputs {
@@ -133,3 +340,72 @@ impl<'a, E: Engine> Circuit<E> for CircomCircuit<E> {
         Ok(())
     }
 }
+
+#[test]
+fn unsatisfied_constraint() {
+    use bellman_ce::pairing::bn256::{Bn256, Fr};
+
+    let fr = |s: &str| Fr::from_str(s).unwrap();
+    // out = a * b, out + 0 = a + b
+    let mut circuit = CircomCircuit::<Bn256> {
+        r1cs: R1CS {
+            num_inputs: 2,
+            num_aux: 2,
+            num_variables: 4,
+            constraints: vec![
+                (vec![(2, fr("1"))], vec![(3, fr("1"))], vec![(1, fr("1"))]),
+                (vec![(0, fr("1"))], vec![(1, fr("1"))], vec![(2, fr("1")), (3, fr("1"))]),
+            ],
+        },
+        witness: Some(vec![fr("1"), fr("4"), fr("2"), fr("2")]),
+        wire_mapping: None,
+        aux_offset: 1,
+        symbols: None,
+    };
+    assert!(circuit.find_unsatisfied_constraint().unwrap().is_none());
+
+    circuit.witness = Some(vec![fr("1"), fr("6"), fr("2"), fr("3")]);
+    let unsatisfied = circuit.find_unsatisfied_constraint().unwrap().unwrap();
+    assert_eq!(unsatisfied.index, 1);
+    assert_eq!(unsatisfied.ab, "6");
+    assert_eq!(unsatisfied.c, "5");
+    assert_eq!(unsatisfied.wires, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn remapped_witness() {
+    use bellman_ce::pairing::bn256::{Bn256, Fr};
+
+    let fr = |s: &str| Fr::from_str(s).unwrap();
+    // out = a * b over wires 0..4, signal labels 2 and 4 are optimized out
+    let base = CircomCircuit::<Bn256> {
+        r1cs: R1CS {
+            num_inputs: 2,
+            num_aux: 2,
+            num_variables: 4,
+            constraints: vec![(vec![(2, fr("1"))], vec![(3, fr("1"))], vec![(1, fr("1"))])],
+        },
+        witness: None,
+        wire_mapping: Some(vec![0, 1, 3, 5]),
+        aux_offset: 1,
+        symbols: None,
+    };
+
+    let mut circuit = base.clone();
+    circuit.set_witness(vec![fr("1"), fr("6"), fr("7"), fr("2"), fr("7"), fr("3")]);
+    assert!(circuit.wire_mapping.is_some());
+    assert_eq!(circuit.get_public_inputs(), Some(vec![fr("6")]));
+    let report = circuit.check_witness().unwrap();
+    assert_eq!(report.expected_witness_len, 6);
+    assert!(report.is_valid());
+
+    let mut circuit = base.clone();
+    circuit.set_witness(vec![fr("1"), fr("6"), fr("2"), fr("3")]);
+    assert!(circuit.wire_mapping.is_none());
+    assert!(circuit.check_witness().unwrap().is_valid());
+
+    let mut circuit = base;
+    circuit.wire_mapping = Some(vec![0, 1, 3]);
+    circuit.set_witness(vec![fr("1"), fr("6"), fr("7"), fr("2"), fr("7"), fr("3")]);
+    assert!(matches!(circuit.find_unsatisfied_constraint(), Err(Error::InvalidWitness(_))));
+}
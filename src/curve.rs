@@ -0,0 +1,85 @@
+// circom compiles circuits over the bn128 scalar field by default, and over bls12-381 with `-p bls12381`.
+// Neither r1cs nor wtns files name their curve, only the prime of its scalar field.
+use bellman_ce::pairing::{
+    bls12_381::Bls12,
+    bn256::Bn256,
+    ff::{PrimeField, PrimeFieldRepr},
+    Engine,
+};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    Bn256,
+    Bls12_381,
+}
+
+/// Scalar field modulus of the engine, in the little endian layout of r1cs and wtns headers.
+pub fn field_prime<E: Engine>() -> Vec<u8> {
+    let mut prime = Vec::new();
+    E::Fr::char().write_le(&mut prime).expect("write to vec");
+    prime
+}
+
+impl Curve {
+    pub fn from_prime(prime: &[u8]) -> Option<Curve> {
+        if prime == field_prime::<Bn256>().as_slice() {
+            Some(Curve::Bn256)
+        } else if prime == field_prime::<Bls12>().as_slice() {
+            Some(Curve::Bls12_381)
+        } else {
+            None
+        }
+    }
+}
+
+/// Calls `f::<E>(args)` with the engine `E` of a curve, e.g. `with_curve!(curve, prove(opts))`.
+#[macro_export]
+macro_rules! with_curve {
+    ($curve:expr, $f:ident($($arg:expr),*)) => {
+        match $curve {
+            $crate::curve::Curve::Bn256 => $f::<$crate::bellman_ce::pairing::bn256::Bn256>($($arg),*),
+            $crate::curve::Curve::Bls12_381 => $f::<$crate::bellman_ce::pairing::bls12_381::Bls12>($($arg),*),
+        }
+    };
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Curve::Bn256 => write!(f, "bn256"),
+            Curve::Bls12_381 => write!(f, "bls12_381"),
+        }
+    }
+}
+
+impl FromStr for Curve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // accept the names used by circom and snarkjs as well
+        match s.to_lowercase().as_str() {
+            "bn256" | "bn254" | "bn128" => Ok(Curve::Bn256),
+            "bls12_381" | "bls12-381" | "bls12381" => Ok(Curve::Bls12_381),
+            _ => Err(format!("unknown curve {}, expected bn256 or bls12_381", s)),
+        }
+    }
+}
+
+#[test]
+fn primes() {
+    assert_eq!(
+        field_prime::<Bn256>(),
+        hex!("010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430").to_vec()
+    );
+    assert_eq!(
+        field_prime::<Bls12>(),
+        hex!("01000000fffffffffe5bfeff02a4bd5305d8a10908d83933487d9d2953a7ed73").to_vec()
+    );
+    assert_eq!(Curve::from_prime(&field_prime::<Bls12>()), Some(Curve::Bls12_381));
+    assert_eq!(Curve::from_prime(&[0u8; 32]), None);
+    assert_eq!("bls12381".parse(), Ok(Curve::Bls12_381));
+    assert_eq!("bn128".parse(), Ok(Curve::Bn256));
+    assert_eq!(with_curve!(Curve::Bls12_381, field_prime()), field_prime::<Bls12>());
+}
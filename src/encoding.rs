@@ -0,0 +1,340 @@
+// Self-describing JSON encoding of proofs and verification keys, the binary one of bellman has no field names.
+//
+// Every document carries `version`, `protocol` ("plonk") and `curve` ("bn256" or "bls12_381"). Field elements are
+// 0x prefixed big endian hex strings, curve points the 0x prefixed hex of their uncompressed encoding, so that a
+// document converts back to exactly the same proof or key.
+//
+// A proof:
+//   { "version": 1, "protocol": "plonk", "curve": "bn256", "n": 1023, "num_inputs": 1, "inputs": [fr],
+//     "commitments": { "wires": [g1; 4], "grand_product": g1, "quotient": [g1; 4] },
+//     "evaluations": { "wires_at_z": [fr; 4], "wires_at_z_omega": [fr; 1], "grand_product_at_z_omega": fr,
+//                      "quotient_at_z": fr, "linearization_at_z": fr, "permutations_at_z": [fr; 3] },
+//     "openings": { "at_z": g1, "at_z_omega": g1 } }
+//
+// A verification key:
+//   { "version": 1, "protocol": "plonk", "curve": "bn256", "n": 1023, "num_inputs": 1,
+//     "selector_commitments": [g1; 6], "next_step_selector_commitments": [g1; 1], "permutation_commitments": [g1; 4],
+//     "non_residues": [fr; 3], "g2_elements": [g2; 2] }
+use bellman_ce::{
+    pairing::{
+        ff::{PrimeField, PrimeFieldRepr},
+        CurveAffine, EncodedPoint, Engine,
+    },
+    plonk::better_cs::{
+        cs::PlonkCsWidth4WithNextStepParams,
+        keys::{write_curve_affine, write_curve_affine_vec, write_fr_vec, Proof, VerificationKey},
+    },
+};
+use byteorder::{BigEndian, WriteBytesExt};
+use std::io::{Read, Write};
+
+use crate::curve::{field_prime, Curve};
+use crate::error::{Error, Result};
+
+pub const ENCODING_VERSION: u32 = 1;
+const PROTOCOL: &str = "plonk";
+
+#[derive(Serialize, Deserialize)]
+pub struct ProofJson {
+    pub version: u32,
+    pub protocol: String,
+    pub curve: String,
+    pub n: usize,
+    pub num_inputs: usize,
+    pub inputs: Vec<String>,
+    pub commitments: ProofCommitments,
+    pub evaluations: ProofEvaluations,
+    pub openings: ProofOpenings,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProofCommitments {
+    pub wires: Vec<String>,
+    pub grand_product: String,
+    pub quotient: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProofEvaluations {
+    pub wires_at_z: Vec<String>,
+    pub wires_at_z_omega: Vec<String>,
+    pub grand_product_at_z_omega: String,
+    pub quotient_at_z: String,
+    pub linearization_at_z: String,
+    pub permutations_at_z: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProofOpenings {
+    pub at_z: String,
+    pub at_z_omega: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VerificationKeyJson {
+    pub version: u32,
+    pub protocol: String,
+    pub curve: String,
+    pub n: usize,
+    pub num_inputs: usize,
+    pub selector_commitments: Vec<String>,
+    pub next_step_selector_commitments: Vec<String>,
+    pub permutation_commitments: Vec<String>,
+    pub non_residues: Vec<String>,
+    pub g2_elements: Vec<String>,
+}
+
+/// Tells the JSON encoding from the binary one, whose first byte is the top byte of a u64 domain size.
+pub fn is_json(data: &[u8]) -> bool {
+    data.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{')
+}
+
+/// Curve named by a JSON proof or verification key, `None` for the binary encoding which does not record it.
+pub fn curve_of(data: &[u8]) -> Result<Option<Curve>> {
+    if !is_json(data) {
+        return Ok(None);
+    }
+    #[derive(Deserialize)]
+    struct Header {
+        curve: String,
+    }
+    let header: Header = serde_json::from_slice(data)?;
+    Ok(Some(header.curve.parse().map_err(Error::Format)?))
+}
+
+fn engine_curve<E: Engine>() -> Result<Curve> {
+    Curve::from_prime(&field_prime::<E>()).ok_or_else(|| Error::FieldMismatch("unsupported curve".to_owned()))
+}
+
+fn check_header<E: Engine>(version: u32, protocol: &str, curve: &str) -> Result<()> {
+    if version != ENCODING_VERSION {
+        return Err(Error::Format(format!("unsupported encoding version {}", version)));
+    }
+    if protocol != PROTOCOL {
+        return Err(Error::Format(format!("unsupported protocol {}", protocol)));
+    }
+    let curve: Curve = curve.parse().map_err(Error::Format)?;
+    let expected = engine_curve::<E>()?;
+    if curve != expected {
+        return Err(Error::FieldMismatch(format!(
+            "the encoding is for {} instead of {}",
+            curve, expected
+        )));
+    }
+    Ok(())
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    let digits = value
+        .strip_prefix("0x")
+        .ok_or_else(|| Error::Format(format!("{} is not 0x prefixed", value)))?;
+    hex::decode(digits).map_err(|e| Error::Format(format!("invalid hex {}: {}", value, e)))
+}
+
+fn encode_fr<F: PrimeField>(value: &F) -> String {
+    let mut bytes = Vec::new();
+    value.into_repr().write_be(&mut bytes).expect("write to vec");
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn decode_fr<F: PrimeField>(value: &str) -> Result<F> {
+    let bytes = decode_hex(value)?;
+    let mut repr = F::Repr::default();
+    if bytes.len() != repr.as_ref().len() * 8 {
+        return Err(Error::Format(format!("field element {} has {} bytes", value, bytes.len())));
+    }
+    repr.read_be(bytes.as_slice())?;
+    F::from_repr(repr).map_err(|e| Error::Format(format!("invalid field element {}: {}", value, e)))
+}
+
+fn encode_point<G: CurveAffine>(point: &G) -> String {
+    format!("0x{}", hex::encode(point.into_uncompressed().as_ref()))
+}
+
+fn decode_point<G: CurveAffine>(value: &str) -> Result<G> {
+    let bytes = decode_hex(value)?;
+    let mut encoded = G::Uncompressed::empty();
+    if bytes.len() != encoded.as_ref().len() {
+        return Err(Error::Format(format!("curve point {} has {} bytes", value, bytes.len())));
+    }
+    encoded.as_mut().copy_from_slice(&bytes);
+    encoded
+        .into_affine()
+        .map_err(|e| Error::Format(format!("invalid curve point {}: {}", value, e)))
+}
+
+fn decode_vec<T, F: Fn(&str) -> Result<T>>(values: &[String], len: usize, name: &str, decode: F) -> Result<Vec<T>> {
+    if values.len() != len {
+        return Err(Error::Format(format!("{} has {} values instead of {}", name, values.len(), len)));
+    }
+    values.iter().map(|value| decode(value)).collect()
+}
+
+pub fn proof_to_json<E: Engine>(proof: &Proof<E, PlonkCsWidth4WithNextStepParams>) -> Result<ProofJson> {
+    Ok(ProofJson {
+        version: ENCODING_VERSION,
+        protocol: PROTOCOL.to_owned(),
+        curve: engine_curve::<E>()?.to_string(),
+        n: proof.n,
+        num_inputs: proof.num_inputs,
+        inputs: proof.input_values.iter().map(encode_fr).collect(),
+        commitments: ProofCommitments {
+            wires: proof.wire_commitments.iter().map(encode_point).collect(),
+            grand_product: encode_point(&proof.grand_product_commitment),
+            quotient: proof.quotient_poly_commitments.iter().map(encode_point).collect(),
+        },
+        evaluations: ProofEvaluations {
+            wires_at_z: proof.wire_values_at_z.iter().map(encode_fr).collect(),
+            wires_at_z_omega: proof.wire_values_at_z_omega.iter().map(encode_fr).collect(),
+            grand_product_at_z_omega: encode_fr(&proof.grand_product_at_z_omega),
+            quotient_at_z: encode_fr(&proof.quotient_polynomial_at_z),
+            linearization_at_z: encode_fr(&proof.linearization_polynomial_at_z),
+            permutations_at_z: proof.permutation_polynomials_at_z.iter().map(encode_fr).collect(),
+        },
+        openings: ProofOpenings {
+            at_z: encode_point(&proof.opening_at_z_proof),
+            at_z_omega: encode_point(&proof.opening_at_z_omega_proof),
+        },
+    })
+}
+
+pub fn proof_from_json<E: Engine>(json: &ProofJson) -> Result<Proof<E, PlonkCsWidth4WithNextStepParams>> {
+    check_header::<E>(json.version, &json.protocol, &json.curve)?;
+    let mut proof = Proof::<E, PlonkCsWidth4WithNextStepParams>::empty();
+    proof.n = json.n;
+    proof.num_inputs = json.num_inputs;
+    proof.input_values = decode_vec(&json.inputs, json.num_inputs, "inputs", decode_fr)?;
+    proof.wire_commitments = decode_vec(&json.commitments.wires, 4, "commitments.wires", decode_point)?;
+    proof.grand_product_commitment = decode_point(&json.commitments.grand_product)?;
+    proof.quotient_poly_commitments = decode_vec(&json.commitments.quotient, 4, "commitments.quotient", decode_point)?;
+    proof.wire_values_at_z = decode_vec(&json.evaluations.wires_at_z, 4, "evaluations.wires_at_z", decode_fr)?;
+    proof.wire_values_at_z_omega = decode_vec(&json.evaluations.wires_at_z_omega, 1, "evaluations.wires_at_z_omega", decode_fr)?;
+    proof.grand_product_at_z_omega = decode_fr(&json.evaluations.grand_product_at_z_omega)?;
+    proof.quotient_polynomial_at_z = decode_fr(&json.evaluations.quotient_at_z)?;
+    proof.linearization_polynomial_at_z = decode_fr(&json.evaluations.linearization_at_z)?;
+    proof.permutation_polynomials_at_z = decode_vec(&json.evaluations.permutations_at_z, 3, "evaluations.permutations_at_z", decode_fr)?;
+    proof.opening_at_z_proof = decode_point(&json.openings.at_z)?;
+    proof.opening_at_z_omega_proof = decode_point(&json.openings.at_z_omega)?;
+    Ok(proof)
+}
+
+pub fn verification_key_to_json<E: Engine>(vk: &VerificationKey<E, PlonkCsWidth4WithNextStepParams>) -> Result<VerificationKeyJson> {
+    Ok(VerificationKeyJson {
+        version: ENCODING_VERSION,
+        protocol: PROTOCOL.to_owned(),
+        curve: engine_curve::<E>()?.to_string(),
+        n: vk.n,
+        num_inputs: vk.num_inputs,
+        selector_commitments: vk.selector_commitments.iter().map(encode_point).collect(),
+        next_step_selector_commitments: vk.next_step_selector_commitments.iter().map(encode_point).collect(),
+        permutation_commitments: vk.permutation_commitments.iter().map(encode_point).collect(),
+        non_residues: vk.non_residues.iter().map(encode_fr).collect(),
+        g2_elements: vk.g2_elements.iter().map(encode_point).collect(),
+    })
+}
+
+pub fn verification_key_from_json<E: Engine>(json: &VerificationKeyJson) -> Result<VerificationKey<E, PlonkCsWidth4WithNextStepParams>> {
+    check_header::<E>(json.version, &json.protocol, &json.curve)?;
+    let selector_commitments = decode_vec(&json.selector_commitments, 6, "selector_commitments", decode_point::<E::G1Affine>)?;
+    let next_step_selector_commitments = decode_vec(
+        &json.next_step_selector_commitments,
+        1,
+        "next_step_selector_commitments",
+        decode_point::<E::G1Affine>,
+    )?;
+    let permutation_commitments = decode_vec(
+        &json.permutation_commitments,
+        4,
+        "permutation_commitments",
+        decode_point::<E::G1Affine>,
+    )?;
+    let non_residues = decode_vec(&json.non_residues, 3, "non_residues", decode_fr::<E::Fr>)?;
+    let g2_elements = decode_vec(&json.g2_elements, 2, "g2_elements", decode_point::<E::G2Affine>)?;
+
+    // the key can't be built field by field outside of bellman, it is read back from its binary form
+    let mut data = Vec::new();
+    data.write_u64::<BigEndian>(json.n as u64)?;
+    data.write_u64::<BigEndian>(json.num_inputs as u64)?;
+    write_curve_affine_vec(&selector_commitments, &mut data)?;
+    write_curve_affine_vec(&next_step_selector_commitments, &mut data)?;
+    write_curve_affine_vec(&permutation_commitments, &mut data)?;
+    write_fr_vec(&non_residues, &mut data)?;
+    write_curve_affine(&g2_elements[0], &mut data)?;
+    write_curve_affine(&g2_elements[1], &mut data)?;
+    Ok(VerificationKey::read(data.as_slice())?)
+}
+
+pub fn write_proof_json<E: Engine, W: Write>(proof: &Proof<E, PlonkCsWidth4WithNextStepParams>, writer: W) -> Result<()> {
+    Ok(serde_json::to_writer_pretty(writer, &proof_to_json(proof)?)?)
+}
+
+pub fn read_proof_json<E: Engine, R: Read>(reader: R) -> Result<Proof<E, PlonkCsWidth4WithNextStepParams>> {
+    proof_from_json(&serde_json::from_reader(reader)?)
+}
+
+/// Converts a proof to the engine `F` of the same curve, e.g. for the bn256 only solidity encoding from code generic
+/// over the engine. Fails with `FieldMismatch` when the curves differ.
+pub fn convert_proof<E: Engine, F: Engine>(
+    proof: &Proof<E, PlonkCsWidth4WithNextStepParams>,
+) -> Result<Proof<F, PlonkCsWidth4WithNextStepParams>> {
+    let (curve, expected) = (engine_curve::<E>()?, engine_curve::<F>()?);
+    if curve != expected {
+        return Err(Error::FieldMismatch(format!("the proof is for {} instead of {}", curve, expected)));
+    }
+    let mut data = Vec::new();
+    proof.write(&mut data)?;
+    Ok(Proof::read(data.as_slice())?)
+}
+
+pub fn write_verification_key_json<E: Engine, W: Write>(vk: &VerificationKey<E, PlonkCsWidth4WithNextStepParams>, writer: W) -> Result<()> {
+    Ok(serde_json::to_writer_pretty(writer, &verification_key_to_json(vk)?)?)
+}
+
+pub fn read_verification_key_json<E: Engine, R: Read>(reader: R) -> Result<VerificationKey<E, PlonkCsWidth4WithNextStepParams>> {
+    verification_key_from_json(&serde_json::from_reader(reader)?)
+}
+
+#[test]
+fn round_trip() {
+    use bellman_ce::kate_commitment::{Crs, CrsForMonomialForm};
+    use bellman_ce::pairing::{
+        bls12_381::Bls12,
+        bn256::{Bn256, Fr},
+    };
+    // out <== a * b, with a = 3 and b = 4
+    let circuit = br#"{"constraints": [[{"2": "1"}, {"3": "1"}, {"1": "1"}]], "nPubInputs": 0, "nOutputs": 1, "nVars": 4}"#;
+    let (r1cs, wire_mapping) = crate::reader::load_r1cs_from_array::<Bn256>(circuit).unwrap();
+    let prover = crate::Prover::builder()
+        .circuit(r1cs, wire_mapping)
+        .srs_monomial(Crs::<Bn256, CrsForMonomialForm>::dummy_crs(256))
+        .build()
+        .unwrap();
+    let witness = ["1", "12", "3", "4"].iter().map(|w| Fr::from_str(w).unwrap()).collect();
+    let proof = prover.prove(witness).unwrap().proof;
+    let vk = prover.verification_key().unwrap();
+
+    let mut json = Vec::new();
+    write_proof_json(&proof, &mut json).unwrap();
+    assert!(is_json(&json));
+    let (mut expected, mut actual) = (Vec::new(), Vec::new());
+    proof.write(&mut expected).unwrap();
+    read_proof_json::<Bn256, _>(json.as_slice()).unwrap().write(&mut actual).unwrap();
+    assert_eq!(actual, expected);
+    assert!(!is_json(&expected));
+    assert_eq!(curve_of(&json).unwrap(), Some(Curve::Bn256));
+    assert!(matches!(read_proof_json::<Bls12, _>(json.as_slice()), Err(Error::FieldMismatch(_))));
+    let mut converted = Vec::new();
+    convert_proof::<Bn256, Bn256>(&proof).unwrap().write(&mut converted).unwrap();
+    assert_eq!(converted, expected);
+    assert!(matches!(convert_proof::<Bn256, Bls12>(&proof), Err(Error::FieldMismatch(_))));
+
+    let mut json = Vec::new();
+    write_verification_key_json(&vk, &mut json).unwrap();
+    let (mut expected, mut actual) = (Vec::new(), Vec::new());
+    vk.write(&mut expected).unwrap();
+    read_verification_key_json::<Bn256, _>(json.as_slice())
+        .unwrap()
+        .write(&mut actual)
+        .unwrap();
+    assert_eq!(actual, expected);
+}
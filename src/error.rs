@@ -0,0 +1,89 @@
+use crate::circom_circuit::UnsatisfiedConstraint;
+use bellman_ce::SynthesisError;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file or stream failed
+    Io(io::Error),
+    /// Malformed or out of range input: circuit, witness, key, proof or setup parameters
+    Format(String),
+    /// The input belongs to another field than the one of the engine, e.g. a bls12-381 circuit read as bn256
+    FieldMismatch(String),
+    /// The universal setup holds fewer powers than the circuit domain needs
+    SrsTooSmall {
+        required: usize,
+        available: usize,
+    },
+    /// The witness does not satisfy the circuit
+    UnsatisfiedConstraint(UnsatisfiedConstraint),
+    /// The witness was checked against the circuit and found invalid, see the logged report for details
+    InvalidWitness(String),
+    Synthesis(SynthesisError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Format(msg) => write!(f, "invalid format: {}", msg),
+            Error::FieldMismatch(msg) => write!(f, "field mismatch: {}", msg),
+            Error::SrsTooSmall { required, available } => {
+                write!(f, "srs too small: the circuit needs {} powers, the srs has {}", required, available)
+            }
+            Error::UnsatisfiedConstraint(c) => write!(f, "{}", c),
+            Error::InvalidWitness(msg) => write!(f, "invalid witness: {}", msg),
+            Error::Synthesis(e) => write!(f, "synthesis error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::UnsatisfiedConstraint(c) => Some(c),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        // bellman and byteorder report malformed and truncated data through io errors
+        match e.kind() {
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => Error::Format(e.to_string()),
+            _ => Error::Io(e),
+        }
+    }
+}
+
+impl From<SynthesisError> for Error {
+    fn from(e: SynthesisError) -> Self {
+        match e {
+            SynthesisError::IoError(e) => e.into(),
+            e => Error::Synthesis(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        if e.is_io() {
+            Error::Io(e.into())
+        } else {
+            Error::Format(e.to_string())
+        }
+    }
+}
+
+#[test]
+fn io_conversion() {
+    let err: Error = io::Error::new(io::ErrorKind::UnexpectedEof, "truncated").into();
+    assert!(matches!(err, Error::Format(_)));
+    let err: Error = io::Error::new(io::ErrorKind::NotFound, "circuit.r1cs").into();
+    assert!(matches!(err, Error::Io(_)));
+}
@@ -0,0 +1,225 @@
+// C API of the shared library, see include/plonkit.h. Only bn256 circuits are supported.
+//
+// Every function that can fail takes a `char **error`. On failure it is set, when not NULL, to a message that the
+// caller frees with `plonkit_error_free`. Buffers returned by the library are freed with `plonkit_buffer_free`.
+use bellman_ce::pairing::bn256::Bn256;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::api::{Prover, Verifier};
+use crate::reader;
+
+pub struct PlonkitProver(Prover<Bn256>);
+
+pub struct PlonkitVerifier(Verifier<Bn256>);
+
+#[repr(C)]
+pub struct PlonkitBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl PlonkitBuffer {
+    fn from_vec(data: Vec<u8>) -> Self {
+        let len = data.len();
+        let data = Box::into_raw(data.into_boxed_slice()) as *mut u8;
+        PlonkitBuffer { data, len }
+    }
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if data.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
+}
+
+unsafe fn maybe_bytes<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if data.is_null() {
+        None
+    } else {
+        Some(std::slice::from_raw_parts(data, len))
+    }
+}
+
+// panics must not unwind into C, they are reported like any other error
+unsafe fn call<T, F: FnOnce() -> crate::Result<T>>(error: *mut *mut c_char, f: F) -> Option<T> {
+    let message = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => return Some(value),
+        Ok(Err(e)) => e.to_string(),
+        Err(panic) => match panic.downcast_ref::<&str>() {
+            Some(s) => s.to_string(),
+            None => panic
+                .downcast_ref::<String>()
+                .cloned()
+                .unwrap_or_else(|| "plonkit panicked".to_owned()),
+        },
+    };
+    if !error.is_null() {
+        *error = CString::new(message.replace('\0', " ")).expect("no nul byte").into_raw();
+    }
+    None
+}
+
+/// Loads a circuit (.r1cs or JSON) and prepares it for proving with the srs. `srs_lagrange` and
+/// `proving_key` are optional and may be NULL. Returns NULL on failure.
+///
+/// # Safety
+/// Every non NULL pointer must point to the given number of readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn plonkit_prover_new(
+    circuit: *const u8,
+    circuit_len: usize,
+    srs_monomial: *const u8,
+    srs_monomial_len: usize,
+    srs_lagrange: *const u8,
+    srs_lagrange_len: usize,
+    proving_key: *const u8,
+    proving_key_len: usize,
+    error: *mut *mut c_char,
+) -> *mut PlonkitProver {
+    let prover = call(error, || {
+        let (r1cs, wire_mapping) = reader::load_r1cs_from_array::<Bn256>(bytes(circuit, circuit_len))?;
+        let mut builder = Prover::builder()
+            .circuit(r1cs, wire_mapping)
+            .srs_monomial(reader::load_key_monomial_form_from_reader(bytes(srs_monomial, srs_monomial_len))?);
+        if let Some(srs_lagrange) = maybe_bytes(srs_lagrange, srs_lagrange_len) {
+            builder = builder.srs_lagrange(reader::load_key_lagrange_form_from_reader(srs_lagrange)?);
+        }
+        if let Some(proving_key) = maybe_bytes(proving_key, proving_key_len) {
+            builder = builder.proving_key(proving_key.to_vec());
+        }
+        builder.build()
+    });
+    match prover {
+        Some(prover) => Box::into_raw(Box::new(PlonkitProver(prover))),
+        None => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// `prover` must come from `plonkit_prover_new` and not be used afterwards. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn plonkit_prover_free(prover: *mut PlonkitProver) {
+    if !prover.is_null() {
+        drop(Box::from_raw(prover));
+    }
+}
+
+/// Writes the verification key of the circuit to `vk`. Returns 0 on success, -1 on failure.
+///
+/// # Safety
+/// `prover` must come from `plonkit_prover_new`, `vk` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn plonkit_prover_verification_key(
+    prover: *const PlonkitProver,
+    vk: *mut PlonkitBuffer,
+    error: *mut *mut c_char,
+) -> c_int {
+    let data = call(error, || {
+        let mut data = Vec::new();
+        (*prover).0.verification_key()?.write(&mut data)?;
+        Ok(data)
+    });
+    match data {
+        Some(data) => {
+            *vk = PlonkitBuffer::from_vec(data);
+            0
+        }
+        None => -1,
+    }
+}
+
+/// Proves a witness in the .wtns format and writes the proof, in the format of `plonkit prove --proof`,
+/// to `proof`. Returns 0 on success, -1 on failure. A prover may be used from several threads at once.
+///
+/// # Safety
+/// `prover` must come from `plonkit_prover_new`, `witness` must point to `witness_len` readable bytes
+/// and `proof` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn plonkit_prove(
+    prover: *const PlonkitProver,
+    witness: *const u8,
+    witness_len: usize,
+    proof: *mut PlonkitBuffer,
+    error: *mut *mut c_char,
+) -> c_int {
+    let data = call(error, || {
+        let witness = reader::load_witness_from_array::<Bn256>(bytes(witness, witness_len).to_vec())?;
+        let artifacts = (*prover).0.prove(witness)?;
+        let mut data = Vec::new();
+        artifacts.proof.write(&mut data)?;
+        Ok(data)
+    });
+    match data {
+        Some(data) => {
+            *proof = PlonkitBuffer::from_vec(data);
+            0
+        }
+        None => -1,
+    }
+}
+
+/// Loads a verification key as written by `plonkit export-verification-key`. Returns NULL on failure.
+///
+/// # Safety
+/// `vk` must point to `vk_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn plonkit_verifier_new(vk: *const u8, vk_len: usize, error: *mut *mut c_char) -> *mut PlonkitVerifier {
+    match call(error, || reader::load_verification_key_from_reader::<Bn256, _>(bytes(vk, vk_len))) {
+        Some(vk) => Box::into_raw(Box::new(PlonkitVerifier(Verifier::from_vk(vk)))),
+        None => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// `verifier` must come from `plonkit_verifier_new` and not be used afterwards. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn plonkit_verifier_free(verifier: *mut PlonkitVerifier) {
+    if !verifier.is_null() {
+        drop(Box::from_raw(verifier));
+    }
+}
+
+/// Returns 1 when the proof is valid, 0 when it is not and -1 when it cannot be checked.
+///
+/// # Safety
+/// `verifier` must come from `plonkit_verifier_new`, `proof` must point to `proof_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn plonkit_verify(
+    verifier: *const PlonkitVerifier,
+    proof: *const u8,
+    proof_len: usize,
+    error: *mut *mut c_char,
+) -> c_int {
+    let correct = call(error, || {
+        let proof = reader::load_proof_from_reader::<Bn256, _>(bytes(proof, proof_len))?;
+        (*verifier).0.verify(&proof)
+    });
+    match correct {
+        Some(true) => 1,
+        Some(false) => 0,
+        None => -1,
+    }
+}
+
+/// # Safety
+/// `buffer` must have been filled by the library and not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn plonkit_buffer_free(buffer: PlonkitBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(std::slice::from_raw_parts_mut(buffer.data, buffer.len)));
+    }
+}
+
+/// # Safety
+/// `error` must have been set by the library and not be freed twice. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn plonkit_error_free(error: *mut c_char) {
+    if !error.is_null() {
+        drop(CString::from_raw(error));
+    }
+}
@@ -0,0 +1,255 @@
+// Transcripts of the Aztec ignition ceremony, transcript00.dat, transcript01.dat and so on. Each one is a manifest of
+// seven big endian u32, its G1 points, its G2 points and a BLAKE2b checksum of everything before. Coordinates are four
+// big endian u64 limbs, least significant first, in montgomery form. The points are the powers x^1, x^2, ... of the
+// secret times the generators, the generators themselves are not part of the transcripts.
+use crate::error::{Error, Result};
+use bellman_ce::kate_commitment::{Crs, CrsForMonomialForm};
+use bellman_ce::pairing::{
+    bn256::{Bn256, Fq, Fq2, G1Affine, G2Affine},
+    ff::PrimeField,
+    CurveAffine, Engine,
+};
+use blake2::{Blake2b, Digest};
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::Read;
+
+pub struct Manifest {
+    pub transcript_number: u32,
+    pub total_transcripts: u32,
+    pub total_g1_points: u32,
+    pub total_g2_points: u32,
+    pub num_g1_points: u32,
+    pub num_g2_points: u32,
+    pub start_from: u32,
+}
+
+const MANIFEST_SIZE: usize = 28;
+const CHECKSUM_SIZE: usize = 64;
+const G1_SIZE: usize = 64;
+const G2_SIZE: usize = 128;
+
+fn read_manifest<R: Read>(mut reader: R) -> Result<Manifest> {
+    Ok(Manifest {
+        transcript_number: reader.read_u32::<BigEndian>()?,
+        total_transcripts: reader.read_u32::<BigEndian>()?,
+        total_g1_points: reader.read_u32::<BigEndian>()?,
+        total_g2_points: reader.read_u32::<BigEndian>()?,
+        num_g1_points: reader.read_u32::<BigEndian>()?,
+        num_g2_points: reader.read_u32::<BigEndian>()?,
+        start_from: reader.read_u32::<BigEndian>()?,
+    })
+}
+
+fn read_fq<R: Read>(mut reader: R) -> Result<Fq> {
+    let mut repr = <Fq as PrimeField>::Repr::default();
+    for limb in repr.as_mut().iter_mut() {
+        *limb = reader.read_u64::<BigEndian>()?;
+    }
+    Fq::from_raw_repr(repr).map_err(|e| Error::Format(e.to_string()))
+}
+
+// Reads a G1 point, checking it is on the curve
+fn read_g1<R: Read>(mut reader: R) -> Result<G1Affine> {
+    let x = read_fq(&mut reader)?;
+    let y = read_fq(&mut reader)?;
+    G1Affine::from_xy_checked(x, y).map_err(|e| Error::Format(format!("invalid G1 point: {}", e)))
+}
+
+// Same as `read_g1` for G2, coordinates are `c0` then `c1`
+fn read_g2<R: Read>(mut reader: R) -> Result<G2Affine> {
+    let x = Fq2 {
+        c0: read_fq(&mut reader)?,
+        c1: read_fq(&mut reader)?,
+    };
+    let y = Fq2 {
+        c0: read_fq(&mut reader)?,
+        c1: read_fq(&mut reader)?,
+    };
+    G2Affine::from_xy_checked(x, y).map_err(|e| Error::Format(format!("invalid G2 point: {}", e)))
+}
+
+// Checks the checksum, number and size of a transcript, then returns its manifest
+fn check_transcript(data: &[u8], number: u32) -> Result<Manifest> {
+    if data.len() < MANIFEST_SIZE + CHECKSUM_SIZE {
+        return Err(Error::Format(format!("transcript {} is truncated", number)));
+    }
+    let (content, checksum) = data.split_at(data.len() - CHECKSUM_SIZE);
+    if Blake2b::digest(content).as_slice() != checksum {
+        return Err(Error::Format(format!("checksum mismatch in transcript {}", number)));
+    }
+
+    let manifest = read_manifest(content)?;
+    if manifest.transcript_number != number {
+        return Err(Error::Format(format!(
+            "transcript {} holds the number {}",
+            number, manifest.transcript_number
+        )));
+    }
+    let expected = MANIFEST_SIZE + manifest.num_g1_points as usize * G1_SIZE + manifest.num_g2_points as usize * G2_SIZE;
+    if content.len() != expected {
+        return Err(Error::Format(format!(
+            "transcript {} has {} bytes instead of {}",
+            number,
+            content.len(),
+            expected
+        )));
+    }
+    Ok(manifest)
+}
+
+/// Assembles a srs of 2^power points in monomial form, `read_transcript(i)` returns the contents of transcript i.
+/// Only as many transcripts as needed are read. Besides checksums, each transcript must start where the previous
+/// one ended, and a pairing check ties its first point to the last point before it.
+pub fn from_transcripts<F: FnMut(u32) -> Result<Vec<u8>>>(mut read_transcript: F, power: u32) -> Result<Crs<Bn256, CrsForMonomialForm>> {
+    let size = 1usize << power;
+    let mut g1_bases = Vec::with_capacity(size);
+    g1_bases.push(G1Affine::one());
+    let mut g2_x = G2Affine::zero();
+    let mut total_transcripts = 0;
+    let mut number = 0;
+    while number == 0 || g1_bases.len() < size {
+        if number > 0 && number == total_transcripts {
+            return Err(Error::SrsTooSmall {
+                required: size,
+                available: g1_bases.len(),
+            });
+        }
+        let data = read_transcript(number)?;
+        let manifest = check_transcript(&data, number)?;
+        if number == 0 {
+            total_transcripts = manifest.total_transcripts;
+            if manifest.total_g1_points as usize + 1 < size {
+                return Err(Error::SrsTooSmall {
+                    required: size,
+                    available: manifest.total_g1_points as usize + 1,
+                });
+            }
+            if manifest.num_g2_points == 0 {
+                return Err(Error::Format("transcript 0 holds no G2 point".to_owned()));
+            }
+            g2_x = read_g2(&data[MANIFEST_SIZE + manifest.num_g1_points as usize * G1_SIZE..])?;
+        } else if manifest.total_transcripts != total_transcripts {
+            return Err(Error::Format(format!(
+                "transcript {} belongs to a ceremony of {} transcripts instead of {}",
+                number, manifest.total_transcripts, total_transcripts
+            )));
+        }
+        if manifest.start_from as usize != g1_bases.len() - 1 {
+            return Err(Error::Format(format!(
+                "transcript {} starts from point {} instead of {}",
+                number,
+                manifest.start_from,
+                g1_bases.len() - 1
+            )));
+        }
+
+        let first = g1_bases.len();
+        let count = std::cmp::min(manifest.num_g1_points as usize, size - first);
+        let mut reader = &data[MANIFEST_SIZE..];
+        for _ in 0..count {
+            g1_bases.push(read_g1(&mut reader)?);
+        }
+        // e(x^(i-1) * G1, x * G2) = e(x^i * G1, G2)
+        if count > 0 && Bn256::pairing(g1_bases[first - 1], g2_x) != Bn256::pairing(g1_bases[first], G2Affine::one()) {
+            return Err(Error::Format(format!(
+                "the first point of transcript {} does not follow the previous one",
+                number
+            )));
+        }
+        number += 1;
+    }
+
+    Ok(Crs::new(g1_bases, vec![G2Affine::one(), g2_x]))
+}
+
+// Splits the powers of tau = 42 into transcripts, the same powers as `Crs::crs_42`
+#[cfg(test)]
+fn sample_transcripts(points_per_transcript: usize, total_transcripts: u32) -> Vec<Vec<u8>> {
+    use bellman_ce::pairing::bn256::Fr;
+    use bellman_ce::pairing::{ff::Field, CurveProjective};
+    use byteorder::WriteBytesExt;
+
+    fn write_fq(data: &mut Vec<u8>, fq: &Fq) {
+        for limb in fq.into_raw_repr().as_ref() {
+            data.write_u64::<BigEndian>(*limb).unwrap();
+        }
+    }
+
+    let tau = Fr::from_str("42").unwrap();
+    let mut tau_i = tau;
+    (0..total_transcripts)
+        .map(|number| {
+            let num_g2_points = if number == 0 { 2 } else { 0 };
+            let mut data = Vec::new();
+            for n in &[
+                number,
+                total_transcripts,
+                points_per_transcript as u32 * total_transcripts,
+                2,
+                points_per_transcript as u32,
+                num_g2_points,
+                number * points_per_transcript as u32,
+            ] {
+                data.write_u32::<BigEndian>(*n).unwrap();
+            }
+            for _ in 0..points_per_transcript {
+                let (x, y) = G1Affine::one().mul(tau_i.into_repr()).into_affine().into_xy_unchecked();
+                write_fq(&mut data, &x);
+                write_fq(&mut data, &y);
+                tau_i.mul_assign(&tau);
+            }
+            let mut tau_j = tau;
+            for _ in 0..num_g2_points {
+                let (x, y) = G2Affine::one().mul(tau_j.into_repr()).into_affine().into_xy_unchecked();
+                for fq in &[x.c0, x.c1, y.c0, y.c1] {
+                    write_fq(&mut data, fq);
+                }
+                tau_j.mul_assign(&tau);
+            }
+            let checksum = Blake2b::digest(&data);
+            data.extend_from_slice(&checksum);
+            data
+        })
+        .collect()
+}
+
+#[test]
+fn sample() {
+    use bellman_ce::worker::Worker;
+
+    let transcripts = sample_transcripts(3, 3);
+    let mut read = Vec::new();
+    let srs = from_transcripts(
+        |i| {
+            read.push(i);
+            Ok(transcripts[i as usize].clone())
+        },
+        3,
+    )
+    .unwrap();
+    assert_eq!(read, vec![0, 1, 2]);
+    let expected = Crs::<Bn256, CrsForMonomialForm>::crs_42(8, &Worker::new());
+    assert_eq!(srs.g1_bases, expected.g1_bases);
+    assert_eq!(srs.g2_monomial_bases, expected.g2_monomial_bases);
+
+    let err = from_transcripts(|i| Ok(transcripts[i as usize].clone()), 4).err().unwrap();
+    assert!(matches!(
+        err,
+        Error::SrsTooSmall {
+            required: 16,
+            available: 10
+        }
+    ));
+}
+
+#[test]
+fn corrupted_transcript() {
+    let mut transcripts = sample_transcripts(3, 3);
+    transcripts[1][MANIFEST_SIZE] ^= 1;
+    let err = from_transcripts(|i| Ok(transcripts[i as usize].clone()), 3).err().unwrap();
+    assert!(err.to_string().contains("checksum mismatch in transcript 1"));
+
+    let transcripts = sample_transcripts(3, 3);
+    let err = from_transcripts(|i| Ok(transcripts[2 - i as usize].clone()), 3).err().unwrap();
+    assert!(err.to_string().contains("transcript 0 holds the number 2"));
+}
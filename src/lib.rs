@@ -1,21 +1,50 @@
 #[macro_use]
 extern crate serde;
+#[cfg(test)]
 #[macro_use]
 extern crate hex_literal;
-extern crate bellman_ce;
+pub extern crate bellman_ce;
 extern crate bellman_vk_codegen;
+extern crate blake2;
 extern crate byteorder;
 extern crate itertools;
+#[cfg(feature = "node")]
+#[macro_use]
+extern crate napi_derive;
 extern crate num_bigint;
 extern crate num_traits;
 extern crate rand;
+extern crate sha2;
 
+pub mod api;
 pub mod circom_circuit;
+pub mod curve;
+pub mod encoding;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod ignition;
+#[cfg(feature = "node")]
+mod node;
 pub mod plonk;
+pub mod ptau_file;
+#[cfg(feature = "python")]
+mod python;
 pub mod r1cs_file;
 pub mod reader;
+pub mod srs;
+pub mod sym_file;
 pub mod transpile;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "witness_calculator")]
+pub mod witness_calculator;
+
+pub use api::{ProofArtifacts, Prover, ProverBuilder, Verifier};
+pub use error::{Error, Result};
+
+#[cfg(feature = "server")]
 pub mod pb {
     tonic::include_proto!("plonkitserver");
 }
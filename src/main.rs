@@ -4,16 +4,28 @@ extern crate clap;
 extern crate plonkit;
 
 use clap::Clap;
+use itertools::Itertools;
 use std::fs::File;
-use std::path::Path;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
 use std::str;
+use std::sync::{mpsc, Arc, Mutex};
 
-use bellman_ce::pairing::bn256::Bn256;
+use bellman_ce::pairing::{bn256::Bn256, Engine};
+use bellman_ce::plonk::better_cs::{cs::PlonkCsWidth4WithNextStepParams, keys::Proof};
 
 use plonkit::circom_circuit::CircomCircuit;
+use plonkit::curve::Curve;
+use plonkit::encoding;
+#[cfg(feature = "server")]
 use plonkit::pb;
 use plonkit::plonk;
 use plonkit::reader;
+use plonkit::srs;
+use plonkit::sym_file::SymbolTable;
+use plonkit::with_curve;
+#[cfg(feature = "witness_calculator")]
+use plonkit::witness_calculator::{self, WitnessCalculator};
 
 #[cfg(feature = "server")]
 mod server;
@@ -36,16 +48,32 @@ enum SubCommand {
     Setup(SetupOpts),
     /// Dump "SRS in lagrange form" from a "SRS in monomial form"
     DumpLagrange(DumpLagrangeOpts),
+    /// Import the tau powers of a snarkjs .ptau file as srs in monomial form
+    ImportPtau(ImportPtauOpts),
+    /// Assemble srs in monomial form from the transcripts of the Aztec ignition ceremony
+    ImportIgnition(ImportIgnitionOpts),
+    /// Check that a srs is a well formed sequence of powers of tau
+    VerifySrs(VerifySrsOpts),
+    /// Inspect and convert srs files without a circuit
+    Srs(SrsOpts),
     /// Serve for SNARK proof
     Serve(ServerOpts),
+    /// Calculate a witness from circuit inputs with the circom wasm witness calculator
+    Witness(WitnessOpts),
+    /// Check a witness against every constraint of the circuit
+    CheckWitness(CheckWitnessOpts),
     /// Generate a SNARK proof
     Prove(ProveOpts),
+    /// Generate SNARK proofs for a batch of witnesses sharing one setup
+    ProveBatch(ProveBatchOpts),
     /// Verify a SNARK proof
     Verify(VerifyOpts),
     /// Generate verifier smart contract
     GenerateVerifier(GenerateVerifierOpts),
     /// Export verifying key
     ExportVerificationKey(ExportVerificationKeyOpts),
+    /// Export proving key
+    ExportProvingKey(ExportProvingKeyOpts),
 }
 
 /// A subcommand for analysing the circuit and outputting some stats
@@ -54,9 +82,21 @@ struct AnalyseOpts {
     /// Circuit R1CS or JSON file [default: circuit.r1cs|circuit.json]
     #[clap(short = "c", long = "circuit")]
     circuit: Option<String>,
+    /// Curve of the circuit, bn256 or bls12_381 [default: detected from the circuit]
+    #[clap(long = "curve")]
+    curve: Option<Curve>,
+    /// Circom debug symbols file [default: the .sym file next to the circuit, if any]
+    #[clap(long = "sym")]
+    sym: Option<String>,
     /// Output file
     #[clap(short = "o", long = "output", default_value = "analyse.json")]
     output: String,
+    /// Number of most expensive constraints to list
+    #[clap(long = "top", default_value = "10")]
+    top: usize,
+    /// Output file for gates per circom component in folded stack format, for rendering as a flamegraph
+    #[clap(long = "folded")]
+    folded: Option<String>,
 }
 
 /// A subcommand for locally trusted setting up Plonk universal srs in monomial form
@@ -68,6 +108,9 @@ struct SetupOpts {
     /// Output file for Plonk universal setup srs in monomial form
     #[clap(short = "m", long = "srs_monomial_form")]
     srs_monomial_form: String,
+    /// Curve, bn256 or bls12_381
+    #[clap(long = "curve", default_value = "bn256")]
+    curve: Curve,
 }
 
 /// A subcommand for dumping SRS in lagrange form
@@ -82,6 +125,115 @@ struct DumpLagrangeOpts {
     /// Circuit R1CS or JSON file [default: circuit.r1cs|circuit.json]
     #[clap(short = "c", long = "circuit")]
     circuit: Option<String>,
+    /// Curve of the circuit, bn256 or bls12_381 [default: detected from the circuit]
+    #[clap(long = "curve")]
+    curve: Option<Curve>,
+    /// Proving key file, skips transpiling and setting up the circuit
+    #[clap(short = "k", long = "proving_key")]
+    proving_key: Option<String>,
+}
+
+/// A subcommand for importing a snarkjs powers of tau file
+#[derive(Clap)]
+struct ImportPtauOpts {
+    /// Source powers of tau file of snarkjs, bn128 only
+    #[clap(long = "ptau")]
+    ptau: String,
+    /// Power_of_two exponent, at most the power of the ptau file
+    #[clap(short = "p", long = "power")]
+    power: u32,
+    /// Output file for Plonk universal setup srs in monomial form
+    #[clap(short = "m", long = "srs_monomial_form")]
+    srs_monomial_form: String,
+}
+
+/// A subcommand for importing the Aztec ignition transcripts
+#[derive(Clap)]
+struct ImportIgnitionOpts {
+    /// Directory holding transcript00.dat, transcript01.dat, ...
+    #[clap(short = "t", long = "transcripts")]
+    transcripts: String,
+    /// Power_of_two exponent
+    #[clap(short = "p", long = "power")]
+    power: u32,
+    /// Output file for Plonk universal setup srs in monomial form
+    #[clap(short = "m", long = "srs_monomial_form")]
+    srs_monomial_form: String,
+}
+
+/// A subcommand for checking a srs
+#[derive(Clap)]
+struct VerifySrsOpts {
+    /// Source file for Plonk universal setup srs in monomial form
+    #[clap(short = "m", long = "srs_monomial_form")]
+    srs_monomial_form: String,
+    /// Source file for Plonk universal setup srs in lagrange form, checked against the monomial form
+    #[clap(short = "l", long = "srs_lagrange_form")]
+    srs_lagrange_form: Option<String>,
+    /// Curve, bn256 or bls12_381
+    #[clap(long = "curve", default_value = "bn256")]
+    curve: Curve,
+}
+
+/// Subcommands for srs files
+#[derive(Clap)]
+struct SrsOpts {
+    #[clap(subcommand)]
+    command: SrsCommand,
+}
+
+#[derive(Clap)]
+enum SrsCommand {
+    /// Show the form, size, power and hash of a srs file
+    Info(SrsInfoOpts),
+    /// Write the first 2^power points of a srs in monomial form as a smaller one
+    Truncate(SrsTruncateOpts),
+    /// Convert a srs in monomial form to lagrange form for a domain of 2^power
+    ToLagrange(SrsToLagrangeOpts),
+}
+
+/// A subcommand for showing a summary of a srs file
+#[derive(Clap)]
+struct SrsInfoOpts {
+    /// Srs file in monomial or lagrange form
+    srs: String,
+    /// Curve, bn256 or bls12_381
+    #[clap(long = "curve", default_value = "bn256")]
+    curve: Curve,
+}
+
+/// A subcommand for truncating a srs in monomial form
+#[derive(Clap)]
+struct SrsTruncateOpts {
+    /// Source file for Plonk universal setup srs in monomial form
+    #[clap(short = "m", long = "srs_monomial_form")]
+    srs_monomial_form: String,
+    /// Power_of_two exponent of the output
+    #[clap(short = "p", long = "power")]
+    power: u32,
+    /// Output file for the truncated srs in monomial form
+    #[clap(short = "o", long = "output")]
+    output: String,
+    /// Curve, bn256 or bls12_381
+    #[clap(long = "curve", default_value = "bn256")]
+    curve: Curve,
+}
+
+/// A subcommand for converting a srs in monomial form to lagrange form
+#[derive(Clap)]
+struct SrsToLagrangeOpts {
+    /// Source file for Plonk universal setup srs in monomial form
+    #[clap(short = "m", long = "srs_monomial_form")]
+    srs_monomial_form: String,
+    /// Power_of_two exponent of the domain
+    #[clap(short = "p", long = "power")]
+    power: u32,
+    /// Output file for Plonk universal setup srs in lagrange form
+    #[clap(short = "l", long = "srs_lagrange_form")]
+    srs_lagrange_form: String,
+    /// Curve, bn256 or bls12_381
+    #[clap(long = "curve", default_value = "bn256")]
+    curve: Curve,
 }
 
 /// A subcommand for running a server and do SNARK proving
@@ -99,6 +251,46 @@ struct ServerOpts {
     /// Circuit R1CS or JSON file [default: circuit.r1cs|circuit.json]
     #[clap(short = "c", long = "circuit")]
     circuit: Option<String>,
+    /// Circom debug symbols file [default: the .sym file next to the circuit, if any]
+    #[clap(long = "sym")]
+    sym: Option<String>,
+    /// Proving key file, skips transpiling and setting up the circuit
+    #[clap(short = "k", long = "proving_key")]
+    proving_key: Option<String>,
+}
+
+/// A subcommand for checking a witness without a setup
+#[derive(Clap)]
+struct CheckWitnessOpts {
+    /// Circuit R1CS or JSON file [default: circuit.r1cs|circuit.json]
+    #[clap(short = "c", long = "circuit")]
+    circuit: Option<String>,
+    /// Curve of the circuit, bn256 or bls12_381 [default: detected from the circuit]
+    #[clap(long = "curve")]
+    curve: Option<Curve>,
+    /// Circom debug symbols file [default: the .sym file next to the circuit, if any]
+    #[clap(long = "sym")]
+    sym: Option<String>,
+    /// Witness BIN or JSON file
+    #[clap(short = "w", long = "witness", default_value = "witness.wtns")]
+    witness: String,
+    /// Output file for the JSON report
+    #[clap(short = "o", long = "output")]
+    output: Option<String>,
+}
+
+/// A subcommand for calculating a witness from circuit inputs
+#[derive(Clap)]
+struct WitnessOpts {
+    /// Witness calculator WASM file compiled by circom
+    #[clap(short = "c", long = "circuit", default_value = "circuit.wasm")]
+    circuit: String,
+    /// Circuit input JSON file
+    #[clap(short = "i", long = "input", default_value = "input.json")]
+    input: String,
+    /// Output file for the witness, in JSON format if it ends with .json
+    #[clap(short = "o", long = "output", default_value = "witness.wtns")]
+    output: String,
 }
 
 /// A subcommand for generating a SNARK proof
@@ -113,10 +305,26 @@ struct ProveOpts {
     /// Circuit R1CS or JSON file [default: circuit.r1cs|circuit.json]
     #[clap(short = "c", long = "circuit")]
     circuit: Option<String>,
+    /// Curve of the circuit, bn256 or bls12_381 [default: detected from the circuit]
+    #[clap(long = "curve")]
+    curve: Option<Curve>,
+    /// Circom debug symbols file [default: the .sym file next to the circuit, if any]
+    #[clap(long = "sym")]
+    sym: Option<String>,
+    /// Proving key file, skips transpiling and setting up the circuit
+    #[clap(short = "k", long = "proving_key")]
+    proving_key: Option<String>,
     /// Witness JSON file
     #[clap(short = "w", long = "witness", default_value = "witness.wtns")]
     witness: String,
-    /// Output file for proof BIN
+    /// Circuit input JSON file, the witness is calculated from it instead of being read from --witness
+    #[clap(long = "input", requires = "wasm")]
+    input: Option<String>,
+    /// Witness calculator WASM file compiled by circom, used with --input
+    #[clap(long = "wasm", requires = "input")]
+    wasm: Option<String>,
+    /// Output file for proof BIN, or for the self-describing proof JSON when it ends with .json (the solidity proof
+    /// json and public input json are then skipped)
     #[clap(short = "p", long = "proof", default_value = "proof.bin")]
     proof: String,
     /// Output file for proof json
@@ -127,21 +335,62 @@ struct ProveOpts {
     publicjson: String,
 }
 
+/// A subcommand for generating SNARK proofs for many witnesses of the same circuit
+#[derive(Clap)]
+struct ProveBatchOpts {
+    /// Source file for Plonk universal setup srs in monomial form
+    #[clap(short = "m", long = "srs_monomial_form")]
+    srs_monomial_form: String,
+    /// Source file for Plonk universal setup srs in lagrange form
+    #[clap(short = "l", long = "srs_lagrange_form")]
+    srs_lagrange_form: Option<String>,
+    /// Circuit R1CS or JSON file [default: circuit.r1cs|circuit.json]
+    #[clap(short = "c", long = "circuit")]
+    circuit: Option<String>,
+    /// Curve of the circuit, bn256 or bls12_381 [default: detected from the circuit]
+    #[clap(long = "curve")]
+    curve: Option<Curve>,
+    /// Circom debug symbols file [default: the .sym file next to the circuit, if any]
+    #[clap(long = "sym")]
+    sym: Option<String>,
+    /// Proving key file, skips transpiling and setting up the circuit
+    #[clap(short = "k", long = "proving_key")]
+    proving_key: Option<String>,
+    /// Directory of witness files (.wtns or .json), or a glob pattern matching them
+    #[clap(short = "w", long = "witnesses")]
+    witnesses: String,
+    /// Output directory, proofs of each witness are saved in a sub-directory named after it
+    #[clap(short = "o", long = "output_dir", default_value = "proofs")]
+    output_dir: String,
+    /// Number of witnesses proven concurrently
+    #[clap(short = "j", long = "jobs", default_value = "1")]
+    jobs: usize,
+}
+
 /// A subcommand for verifying a SNARK proof
 #[derive(Clap)]
 struct VerifyOpts {
-    /// Proof BIN file
+    /// Proof BIN or self-describing JSON file
     #[clap(short = "p", long = "proof", default_value = "proof.bin")]
     proof: String,
-    /// Verification key file
+    /// Verification key BIN or self-describing JSON file
     #[clap(short = "v", long = "verification_key", default_value = "vk.bin")]
     vk: String,
+    /// Proof json written by `prove`, verified with --publicjson instead of --proof (bn256 only)
+    #[clap(short = "j", long = "proofjson", requires = "publicjson")]
+    proofjson: Option<String>,
+    /// Public input json written by `prove`, used with --proofjson
+    #[clap(short = "i", long = "publicjson", requires = "proofjson")]
+    publicjson: Option<String>,
+    /// Curve, bn256 or bls12_381 [default: read from a JSON proof, else bn256]
+    #[clap(long = "curve")]
+    curve: Option<Curve>,
 }
 
 /// A subcommand for generating a Solidity verifier smart contract
 #[derive(Clap)]
 struct GenerateVerifierOpts {
-    /// Verification key file
+    /// Verification key BIN or self-describing JSON file
     #[clap(short = "v", long = "verification_key", default_value = "vk.bin")]
     vk: String,
     /// Output solidity file
@@ -158,11 +407,31 @@ struct ExportVerificationKeyOpts {
     /// Circuit R1CS or JSON file [default: circuit.r1cs|circuit.json]
     #[clap(short = "c", long = "circuit")]
     circuit: Option<String>,
-    /// Output verifying key file
+    /// Curve of the circuit, bn256 or bls12_381 [default: detected from the circuit]
+    #[clap(long = "curve")]
+    curve: Option<Curve>,
+    /// Proving key file, skips transpiling and setting up the circuit
+    #[clap(short = "k", long = "proving_key")]
+    proving_key: Option<String>,
+    /// Output verifying key file, in the self-describing JSON encoding when it ends with .json
     #[clap(short = "v", long = "vk", default_value = "vk.bin")]
     vk: String,
 }
 
+/// A subcommand for exporting the transpiled circuit setup as a proving key
+#[derive(Clap)]
+struct ExportProvingKeyOpts {
+    /// Circuit R1CS or JSON file [default: circuit.r1cs|circuit.json]
+    #[clap(short = "c", long = "circuit")]
+    circuit: Option<String>,
+    /// Curve of the circuit, bn256 or bls12_381 [default: detected from the circuit]
+    #[clap(long = "curve")]
+    curve: Option<Curve>,
+    /// Output proving key file
+    #[clap(short = "k", long = "proving_key", default_value = "pk.bin")]
+    proving_key: String,
+}
+
 fn main() {
     // Always print backtrace on panic.
     ::std::env::set_var("RUST_BACKTRACE", "1");
@@ -177,59 +446,179 @@ fn main() {
     env_logger::init();
 
     let opts: Opts = Opts::parse();
+    if let Err(e) = run(opts) {
+        log::error!("{:#}", e);
+        std::process::exit(exit_code(&e));
+    }
+}
+
+fn run(opts: Opts) -> Result<(), anyhow::Error> {
     match opts.command {
         SubCommand::Analyse(o) => {
-            analyse(o);
+            with_curve!(circuit_curve(&o.circuit, o.curve)?, analyse(o))
         }
         SubCommand::Setup(o) => {
-            setup(o);
+            with_curve!(o.curve, setup(o))
         }
         SubCommand::DumpLagrange(o) => {
-            dump_lagrange(o);
+            with_curve!(circuit_curve(&o.circuit, o.curve)?, dump_lagrange(o))
         }
-        SubCommand::Serve(o) => {
-            serve(o);
+        SubCommand::ImportPtau(o) => import_ptau(o),
+        SubCommand::ImportIgnition(o) => import_ignition(o),
+        SubCommand::VerifySrs(o) => {
+            with_curve!(o.curve, verify_srs(o))
+        }
+        SubCommand::Srs(o) => match o.command {
+            SrsCommand::Info(o) => {
+                with_curve!(o.curve, srs_info(o))
+            }
+            SrsCommand::Truncate(o) => {
+                with_curve!(o.curve, srs_truncate(o))
+            }
+            SrsCommand::ToLagrange(o) => {
+                with_curve!(o.curve, srs_to_lagrange(o))
+            }
+        },
+        SubCommand::Serve(o) => serve(o),
+        SubCommand::Witness(o) => witness(o),
+        SubCommand::CheckWitness(o) => {
+            with_curve!(circuit_curve(&o.circuit, o.curve)?, check_witness(o))
         }
         SubCommand::Prove(o) => {
-            prove(o);
+            with_curve!(circuit_curve(&o.circuit, o.curve)?, prove(o))
         }
-        SubCommand::Verify(o) => {
-            verify(o);
+        SubCommand::ProveBatch(o) => {
+            with_curve!(circuit_curve(&o.circuit, o.curve)?, prove_batch(o))
         }
-        SubCommand::GenerateVerifier(o) => {
-            generate_verifier(o);
+        SubCommand::Verify(o) => {
+            with_curve!(verify_curve(&o)?, verify(o))
         }
+        SubCommand::GenerateVerifier(o) => generate_verifier(o),
         SubCommand::ExportVerificationKey(o) => {
-            export_vk(o);
+            with_curve!(circuit_curve(&o.circuit, o.curve)?, export_vk(o))
+        }
+        SubCommand::ExportProvingKey(o) => {
+            with_curve!(circuit_curve(&o.circuit, o.curve)?, export_pk(o))
         }
     }
 }
 
-fn analyse(opts: AnalyseOpts) {
+/// Maps library errors to distinct exit codes, anything else exits with 1
+fn exit_code(err: &anyhow::Error) -> i32 {
+    match err.downcast_ref::<plonkit::Error>() {
+        Some(plonkit::Error::Io(_)) => exitcode::IOERR,
+        Some(plonkit::Error::Format(_)) => exitcode::NOINPUT,
+        Some(plonkit::Error::FieldMismatch(_)) => exitcode::USAGE,
+        Some(plonkit::Error::SrsTooSmall { .. }) => exitcode::CONFIG,
+        Some(plonkit::Error::UnsatisfiedConstraint(_)) | Some(plonkit::Error::InvalidWitness(_)) => exitcode::DATAERR,
+        Some(plonkit::Error::Synthesis(_)) => exitcode::SOFTWARE,
+        None => 1,
+    }
+}
+
+fn analyse<E: Engine>(opts: AnalyseOpts) -> Result<(), anyhow::Error> {
     let circuit_file = resolve_circuit_file(opts.circuit);
     log::info!("Loading circuit from {}...", circuit_file);
+    let (r1cs, _) = reader::load_r1cs::<E>(&circuit_file)?;
     let circuit = CircomCircuit {
-        r1cs: reader::load_r1cs(&circuit_file),
+        r1cs,
         witness: None,
         wire_mapping: None,
         aux_offset: plonk::AUX_OFFSET,
+        symbols: load_symbols(&circuit_file, opts.sym)?,
     };
-    let mut stats = plonk::analyse(circuit).expect("analyse failed");
-    let writer = File::create(&opts.output).unwrap();
-    serde_json::to_writer_pretty(writer, &stats).expect("write failed");
+    let mut stats = plonk::analyse(circuit, opts.top)?;
+    if stats.resources.min_srs_power > plonk::SETUP_MAX_POW2 {
+        log::warn!(
+            "circuit needs a srs of power {}, more than the supported {}",
+            stats.resources.min_srs_power,
+            plonk::SETUP_MAX_POW2
+        );
+    }
+    if stats.resources.near_domain_boundary {
+        log::warn!(
+            "only {} gates left before the domain grows to 2^{}",
+            stats.resources.free_gates,
+            stats.resources.min_srs_power + 1
+        );
+    }
+    let writer = File::create(&opts.output)?;
+    serde_json::to_writer_pretty(writer, &stats)?;
+    if let Some(folded) = opts.folded {
+        std::fs::write(&folded, stats.folded_stacks())?;
+        log::info!("folded stacks output to {}", folded);
+    }
     stats.constraint_stats.clear();
+    stats.component_stats.truncate(10);
     log::info!(
         "analyse result: {}",
         serde_json::to_string_pretty(&stats).unwrap_or_else(|_| "<failed>".to_owned())
     );
     log::info!("output to {}", opts.output);
+    Ok(())
 }
 
-fn setup(opts: SetupOpts) {
-    let srs = plonk::gen_key_monomial_form::<Bn256>(opts.power).unwrap();
-    let writer = File::create(&opts.srs_monomial_form).unwrap();
-    srs.write(writer).unwrap();
+fn setup<E: Engine>(opts: SetupOpts) -> Result<(), anyhow::Error> {
+    let srs = plonk::gen_key_monomial_form::<E>(opts.power)?;
+    let writer = File::create(&opts.srs_monomial_form)?;
+    srs.write(writer)?;
     log::info!("srs_monomial_form saved to {}", opts.srs_monomial_form);
+    Ok(())
+}
+
+fn import_ptau(opts: ImportPtauOpts) -> Result<(), anyhow::Error> {
+    log::info!("Loading powers of tau from {}...", opts.ptau);
+    let srs = reader::load_key_monomial_form_from_ptau(&opts.ptau, opts.power)?;
+    let writer = BufWriter::new(File::create(&opts.srs_monomial_form)?);
+    srs.write(writer)?;
+    log::info!("srs_monomial_form saved to {}", opts.srs_monomial_form);
+    Ok(())
+}
+
+fn import_ignition(opts: ImportIgnitionOpts) -> Result<(), anyhow::Error> {
+    let srs = reader::load_key_monomial_form_from_ignition(&opts.transcripts, opts.power)?;
+    let writer = BufWriter::new(File::create(&opts.srs_monomial_form)?);
+    srs.write(writer)?;
+    log::info!("srs_monomial_form saved to {}", opts.srs_monomial_form);
+    Ok(())
+}
+
+fn verify_srs<E: Engine>(opts: VerifySrsOpts) -> Result<(), anyhow::Error> {
+    log::info!("Loading srs_monomial_form from {}...", opts.srs_monomial_form);
+    let key_monomial_form = reader::load_key_monomial_form::<E>(&opts.srs_monomial_form)?;
+    srs::check_monomial_form(&key_monomial_form)?;
+    log::info!("srs_monomial_form holds {} valid powers of tau", key_monomial_form.g1_bases.len());
+    if let Some(key_lagrange_form) = reader::maybe_load_key_lagrange_form::<E>(opts.srs_lagrange_form)? {
+        srs::check_lagrange_form(&key_monomial_form, &key_lagrange_form)?;
+        log::info!(
+            "srs_lagrange_form matches srs_monomial_form for a domain of {}",
+            key_lagrange_form.g1_bases.len()
+        );
+    }
+    Ok(())
+}
+
+fn srs_info<E: Engine>(opts: SrsInfoOpts) -> Result<(), anyhow::Error> {
+    let info = srs::info::<E, _>(BufReader::new(File::open(&opts.srs)?))?;
+    log::info!("srs info: {}", serde_json::to_string_pretty(&info)?);
+    Ok(())
+}
+
+fn srs_truncate<E: Engine>(opts: SrsTruncateOpts) -> Result<(), anyhow::Error> {
+    let reader = BufReader::new(File::open(&opts.srs_monomial_form)?);
+    let writer = BufWriter::new(File::create(&opts.output)?);
+    srs::truncate::<E, _, _>(reader, writer, opts.power)?;
+    log::info!("srs_monomial_form of power {} saved to {}", opts.power, opts.output);
+    Ok(())
+}
+
+fn srs_to_lagrange<E: Engine>(opts: SrsToLagrangeOpts) -> Result<(), anyhow::Error> {
+    let key_monomial_form = reader::load_key_monomial_form::<E>(&opts.srs_monomial_form)?;
+    let key_lagrange_form = srs::to_lagrange_form(&key_monomial_form, opts.power)?;
+    let writer = BufWriter::new(File::create(&opts.srs_lagrange_form)?);
+    key_lagrange_form.write(writer)?;
+    log::info!("srs_lagrange_form saved to {}", opts.srs_lagrange_form);
+    Ok(())
 }
 
 fn resolve_circuit_file(filename: Option<String>) -> String {
@@ -245,51 +634,98 @@ fn resolve_circuit_file(filename: Option<String>) -> String {
     }
 }
 
-fn dump_lagrange(opts: DumpLagrangeOpts) {
+fn circuit_curve(circuit: &Option<String>, curve: Option<Curve>) -> Result<Curve, anyhow::Error> {
+    match curve {
+        Some(curve) => Ok(curve),
+        None => Ok(reader::load_r1cs_curve(&resolve_circuit_file(circuit.clone()))?.unwrap_or(Curve::Bn256)),
+    }
+}
+
+fn verify_curve(opts: &VerifyOpts) -> Result<Curve, anyhow::Error> {
+    match (opts.curve, &opts.proofjson) {
+        (Some(curve), _) => Ok(curve),
+        // the solidity encoding only exists for bn256
+        (None, Some(_)) => Ok(Curve::Bn256),
+        (None, None) => Ok(encoding::curve_of(&std::fs::read(&opts.proof)?)?.unwrap_or(Curve::Bn256)),
+    }
+}
+
+fn prepare_setup<E: Engine>(
+    circuit: &CircomCircuit<E>,
+    proving_key: Option<String>,
+    srs_monomial_form: &str,
+    srs_lagrange_form: Option<String>,
+) -> plonkit::Result<plonk::SetupForProver<E>> {
+    let key_monomial_form = reader::load_key_monomial_form(srs_monomial_form)?;
+    let key_lagrange_form = reader::maybe_load_key_lagrange_form(srs_lagrange_form)?;
+    match proving_key {
+        Some(filename) => {
+            log::info!("Loading proving key from {}...", filename);
+            let reader = BufReader::new(File::open(&filename)?);
+            plonk::SetupForProver::load(reader, &circuit.r1cs, key_monomial_form, key_lagrange_form)
+        }
+        None => plonk::SetupForProver::prepare_setup_for_prover(circuit.clone(), key_monomial_form, key_lagrange_form),
+    }
+}
+
+fn load_symbols(circuit_file: &str, sym_file: Option<String>) -> plonkit::Result<Option<Arc<SymbolTable>>> {
+    let sym_file = match sym_file.or_else(|| {
+        let default_file = Path::new(circuit_file).with_extension("sym");
+        if default_file.exists() {
+            Some(default_file.to_string_lossy().into_owned())
+        } else {
+            None
+        }
+    }) {
+        Some(sym_file) => sym_file,
+        None => return Ok(None),
+    };
+    log::info!("Loading debug symbols from {}...", sym_file);
+    Ok(Some(Arc::new(reader::load_sym(&sym_file)?)))
+}
+
+fn dump_lagrange<E: Engine>(opts: DumpLagrangeOpts) -> Result<(), anyhow::Error> {
     let circuit_file = resolve_circuit_file(opts.circuit);
     log::info!("Loading circuit from {}...", circuit_file);
+    let (r1cs, _) = reader::load_r1cs::<E>(&circuit_file)?;
     let circuit = CircomCircuit {
-        r1cs: reader::load_r1cs(&circuit_file),
+        r1cs,
         witness: None,
         wire_mapping: None,
         aux_offset: plonk::AUX_OFFSET,
+        symbols: None,
     };
 
-    let setup = plonk::SetupForProver::prepare_setup_for_prover(circuit, reader::load_key_monomial_form(&opts.srs_monomial_form), None)
-        .expect("prepare err");
+    let setup = prepare_setup(&circuit, opts.proving_key, &opts.srs_monomial_form, None)?;
 
     let key_lagrange_form = setup.get_srs_lagrange_form_from_monomial_form();
-    let writer = File::create(&opts.srs_lagrange_form).unwrap();
-    key_lagrange_form.write(writer).unwrap();
+    let writer = File::create(&opts.srs_lagrange_form)?;
+    key_lagrange_form.write(writer)?;
     log::info!("srs_lagrange_form saved to {}", opts.srs_lagrange_form);
+    Ok(())
 }
 
 #[cfg(feature = "server")]
-fn serve(opts: ServerOpts) {
+fn serve(opts: ServerOpts) -> Result<(), anyhow::Error> {
     let circuit_file = resolve_circuit_file(opts.circuit);
     log::info!("Loading circuit from {}...", circuit_file);
+    let (r1cs, wire_mapping) = reader::load_r1cs::<Bn256>(&circuit_file)?;
     let circuit_base = CircomCircuit {
-        r1cs: reader::load_r1cs(&circuit_file),
+        r1cs,
         witness: None,
-        wire_mapping: None,
+        wire_mapping,
         aux_offset: plonk::AUX_OFFSET,
+        symbols: load_symbols(&circuit_file, opts.sym)?,
     };
 
-    let srs_monomial_form = opts.srs_monomial_form;
-    let srs_lagrange_form = opts.srs_lagrange_form;
+    // prepared before the server starts, so that a bad key or srs exits with its error code
+    let setup = prepare_setup(&circuit_base, opts.proving_key, &opts.srs_monomial_form, opts.srs_lagrange_form)?;
 
     let builder = move || -> server::ServerCore {
-        let setup = plonk::SetupForProver::prepare_setup_for_prover(
-            circuit_base.clone(),
-            reader::load_key_monomial_form(&srs_monomial_form),
-            reader::maybe_load_key_lagrange_form(srs_lagrange_form),
-        )
-        .expect("prepare err");
-
         Box::new(move |witness: Vec<u8>, validate_only: bool| -> server::ServerResult {
             let mut circut = circuit_base.clone();
             match reader::load_witness_from_array::<Bn256>(witness) {
-                Ok(witness) => circut.witness = Some(witness),
+                Ok(witness) => circut.set_witness(witness),
                 err => return server::ServerResult::new(validate_only).any_error(err),
             }
 
@@ -328,96 +764,482 @@ fn serve(opts: ServerOpts) {
         server_addr: opts.srv_addr,
         build_core: Box::new(builder),
     });
+    Ok(())
 }
 
 #[cfg(not(feature = "server"))]
-fn serve(opts: ServerOpts) {
+fn serve(opts: ServerOpts) -> Result<(), anyhow::Error> {
     log::info!(
-        "Binary is not built with server feature: {:?}, {:?}, {:?}, {}",
+        "Binary is not built with server feature: {:?}, {:?}, {:?}, {:?}, {:?}, {}",
         opts.srv_addr,
         opts.circuit,
+        opts.sym,
+        opts.proving_key,
         opts.srs_lagrange_form,
         opts.srs_monomial_form
     );
+    Ok(())
 }
 
-fn prove(opts: ProveOpts) {
+fn check_witness<E: Engine>(opts: CheckWitnessOpts) -> Result<(), anyhow::Error> {
     let circuit_file = resolve_circuit_file(opts.circuit);
     log::info!("Loading circuit from {}...", circuit_file);
-    let circuit = CircomCircuit {
-        r1cs: reader::load_r1cs(&circuit_file),
-        witness: Some(reader::load_witness_from_file::<Bn256>(&opts.witness)),
-        wire_mapping: None,
+    let (r1cs, wire_mapping) = reader::load_r1cs::<E>(&circuit_file)?;
+    let mut circuit = CircomCircuit {
+        r1cs,
+        witness: None,
+        wire_mapping,
         aux_offset: plonk::AUX_OFFSET,
+        symbols: load_symbols(&circuit_file, opts.sym)?,
     };
+    circuit.set_witness(reader::load_witness_from_file::<E>(&opts.witness)?);
 
-    let setup = plonk::SetupForProver::prepare_setup_for_prover(
-        circuit.clone(),
-        reader::load_key_monomial_form(&opts.srs_monomial_form),
-        reader::maybe_load_key_lagrange_form(opts.srs_lagrange_form),
+    let report = circuit.check_witness()?;
+    if report.witness_len != report.expected_witness_len {
+        log::error!(
+            "witness has {} values, circuit expects {}",
+            report.witness_len,
+            report.expected_witness_len
+        );
+    }
+    if !report.wire_0_is_one {
+        log::error!("witness value of wire 0 must be 1");
+    }
+    for constraint in &report.unsatisfied_constraints {
+        log::error!("{}", constraint);
+    }
+    log::info!(
+        "{} of {} constraints unsatisfied",
+        report.unsatisfied_constraints.len(),
+        report.num_constraints
+    );
+    if let Some(output) = opts.output {
+        let writer = File::create(&output)?;
+        serde_json::to_writer_pretty(writer, &report)?;
+        log::info!("Report saved to {}", output);
+    }
+
+    if report.witness_len != report.expected_witness_len {
+        return Err(plonkit::Error::InvalidWitness("wrong number of values".to_owned()).into());
+    }
+    if !report.wire_0_is_one {
+        return Err(plonkit::Error::InvalidWitness("wire 0 is not 1".to_owned()).into());
+    }
+    if !report.unsatisfied_constraints.is_empty() {
+        return Err(plonkit::Error::InvalidWitness(format!(
+            "{} of {} constraints unsatisfied",
+            report.unsatisfied_constraints.len(),
+            report.num_constraints
+        ))
+        .into());
+    }
+    log::info!("Witness is valid.");
+    Ok(())
+}
+
+#[cfg(feature = "witness_calculator")]
+fn load_witness_calculator(wasm: &str) -> Result<WitnessCalculator, anyhow::Error> {
+    log::info!("Loading witness calculator from {}...", wasm);
+    WitnessCalculator::from_file(wasm)
+}
+
+#[cfg(feature = "witness_calculator")]
+fn run_witness_calculator<E: Engine>(mut calculator: WitnessCalculator, input: &str) -> Result<Vec<E::Fr>, anyhow::Error> {
+    let reader = BufReader::new(File::open(input)?);
+    let inputs = witness_calculator::load_inputs_from_json(reader)?;
+    log::info!("Calculating witness...");
+    calculator.calculate_witness::<E>(&inputs)
+}
+
+#[cfg(feature = "witness_calculator")]
+fn calculate_witness<E: Engine>(wasm: &str, input: &str) -> Result<Vec<E::Fr>, anyhow::Error> {
+    run_witness_calculator::<E>(load_witness_calculator(wasm)?, input)
+}
+
+#[cfg(not(feature = "witness_calculator"))]
+fn calculate_witness<E: Engine>(wasm: &str, input: &str) -> Result<Vec<E::Fr>, anyhow::Error> {
+    anyhow::bail!(
+        "plonkit was built without the witness_calculator feature, cannot run {} on {}",
+        wasm,
+        input
     )
-    .expect("prepare err");
+}
+
+#[cfg(feature = "witness_calculator")]
+fn witness(opts: WitnessOpts) -> Result<(), anyhow::Error> {
+    let calculator = load_witness_calculator(&opts.circuit)?;
+    let curve = calculator
+        .curve()
+        .ok_or_else(|| plonkit::Error::FieldMismatch("the witness calculator is for an unsupported curve".to_owned()))?;
+    with_curve!(curve, save_witness(calculator, opts))
+}
+
+#[cfg(not(feature = "witness_calculator"))]
+fn witness(opts: WitnessOpts) -> Result<(), anyhow::Error> {
+    calculate_witness::<Bn256>(&opts.circuit, &opts.input)?;
+    Ok(())
+}
+
+#[cfg(feature = "witness_calculator")]
+fn save_witness<E: Engine>(calculator: WitnessCalculator, opts: WitnessOpts) -> Result<(), anyhow::Error> {
+    let witness = run_witness_calculator::<E>(calculator, &opts.input)?;
+    let writer = BufWriter::new(File::create(&opts.output)?);
+    if opts.output.ends_with(".json") {
+        use bellman_ce::pairing::ff::PrimeField;
+        let values = witness.iter().map(|w| plonkit::utils::repr_to_big(w.into_repr())).collect_vec();
+        serde_json::to_writer(writer, &values)?;
+    } else {
+        reader::write_witness_to_bin_writer::<E, _>(&witness, writer)?;
+    }
+    log::info!("Witness saved to {}", opts.output);
+    Ok(())
+}
+
+fn prove<E: Engine>(opts: ProveOpts) -> Result<(), anyhow::Error> {
+    let circuit_file = resolve_circuit_file(opts.circuit);
+    log::info!("Loading circuit from {}...", circuit_file);
+    let witness = match (opts.input, opts.wasm) {
+        (Some(input), Some(wasm)) => calculate_witness::<E>(&wasm, &input)?,
+        _ => reader::load_witness_from_file::<E>(&opts.witness)?,
+    };
+    let (r1cs, wire_mapping) = reader::load_r1cs::<E>(&circuit_file)?;
+    let mut circuit = CircomCircuit {
+        r1cs,
+        witness: None,
+        wire_mapping,
+        aux_offset: plonk::AUX_OFFSET,
+        symbols: load_symbols(&circuit_file, opts.sym)?,
+    };
+    circuit.set_witness(witness);
+
+    let setup = prepare_setup(&circuit, opts.proving_key, &opts.srs_monomial_form, opts.srs_lagrange_form)?;
 
     log::info!("Proving...");
-    let proof = setup.prove(circuit).unwrap();
-    let writer = File::create(&opts.proof).unwrap();
-    proof.write(writer).unwrap();
-    log::info!("Proof saved to {}", opts.proof);
+    let proof = setup.prove(circuit)?;
+    save_proof(&proof, &opts.proof, &opts.proofjson, &opts.publicjson)
+}
+
+fn save_proof<E: Engine>(
+    proof: &Proof<E, PlonkCsWidth4WithNextStepParams>,
+    proof_file: &str,
+    proofjson: &str,
+    publicjson: &str,
+) -> Result<(), anyhow::Error> {
+    if proof_file.ends_with(".json") {
+        encoding::write_proof_json(proof, BufWriter::new(File::create(proof_file)?))?;
+        log::info!("Proof saved to {}", proof_file);
+        // the default --proofjson is proof.json too, the solidity proof json would overwrite it
+        log::info!("Proof is saved as json, skip {} and {}", proofjson, publicjson);
+        return Ok(());
+    }
+    proof.write(File::create(proof_file)?)?;
+    log::info!("Proof saved to {}", proof_file);
 
     cfg_if::cfg_if! {
         if #[cfg(feature = "solidity")] {
+            // the solidity verifier and its proof encoding only exist for bn256
+            let proof = match encoding::convert_proof::<E, Bn256>(proof) {
+                Ok(proof) => proof,
+                Err(plonkit::Error::FieldMismatch(_)) => {
+                    log::info!("Proof json is only supported on bn256, skip {} and {}", proofjson, publicjson);
+                    return Ok(());
+                }
+                Err(err) => return Err(err.into()),
+            };
             let (inputs, serialized_proof) = bellman_vk_codegen::serialize_proof(&proof);
-            let ser_proof_str = serde_json::to_string_pretty(&serialized_proof).unwrap();
-            let ser_inputs_str = serde_json::to_string_pretty(&inputs).unwrap();
-            std::fs::write(&opts.proofjson, ser_proof_str.as_bytes()).expect("save proofjson err");
-            log::info!("Proof json saved to {}", opts.proofjson);
-            std::fs::write(&opts.publicjson, ser_inputs_str.as_bytes()).expect("save publicjson err");
-            log::info!("Public input json saved to {}", opts.publicjson);
+            let ser_proof_str = serde_json::to_string_pretty(&serialized_proof)?;
+            let ser_inputs_str = serde_json::to_string_pretty(&inputs)?;
+            std::fs::write(proofjson, ser_proof_str.as_bytes())?;
+            log::info!("Proof json saved to {}", proofjson);
+            std::fs::write(publicjson, ser_inputs_str.as_bytes())?;
+            log::info!("Public input json saved to {}", publicjson);
+        } else {
+            log::info!("Binary is not built with solidity feature, skip {} and {}", proofjson, publicjson);
         }
     }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct BatchEntry {
+    witness: String,
+    is_valid: bool,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    error_msg: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    output_dir: String,
+    time_cost_secs: f64,
+}
+
+#[derive(serde::Serialize)]
+struct BatchManifest {
+    num_witnesses: usize,
+    num_proven: usize,
+    num_failed: usize,
+    entries: Vec<BatchEntry>,
+}
+
+fn collect_witness_files(witnesses: &str) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let path = Path::new(witnesses);
+    let mut files = if path.is_dir() {
+        std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.is_file() && matches!(p.extension().and_then(|e| e.to_str()), Some("wtns") | Some("json")))
+            .collect_vec()
+    } else {
+        glob::glob(witnesses)?.filter_map(Result::ok).filter(|p| p.is_file()).collect_vec()
+    };
+    files.sort();
+    Ok(files)
+}
+
+// Names the output directory of each witness after its file stem. Stems must be unique, a.wtns and a.json
+// would otherwise overwrite each other's proofs
+fn batch_output_dirs(witness_files: &[PathBuf]) -> Result<Vec<String>, anyhow::Error> {
+    let mut seen = std::collections::BTreeMap::new();
+    witness_files
+        .iter()
+        .map(|witness_file| {
+            let stem = witness_file
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if let Some(other) = seen.insert(stem.clone(), witness_file) {
+                anyhow::bail!(
+                    "{} and {} would both write to the output directory {}",
+                    other.display(),
+                    witness_file.display(),
+                    stem
+                );
+            }
+            Ok(stem)
+        })
+        .collect()
+}
+
+fn prove_witness<E: Engine>(
+    setup: &plonk::SetupForProver<E>,
+    circuit_base: &CircomCircuit<E>,
+    witness_file: &Path,
+    output_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    let mut circuit = circuit_base.clone();
+    circuit.set_witness(reader::load_witness_from_file::<E>(&witness_file.to_string_lossy())?);
+    let proof = setup.prove(circuit)?;
+    std::fs::create_dir_all(output_dir)?;
+    let output_file = |name: &str| output_dir.join(name).to_string_lossy().into_owned();
+    save_proof(
+        &proof,
+        &output_file("proof.bin"),
+        &output_file("proof.json"),
+        &output_file("public.json"),
+    )
+}
+
+fn prove_batch<E: Engine>(opts: ProveBatchOpts) -> Result<(), anyhow::Error>
+where
+    plonk::SetupForProver<E>: Send + Sync,
+    CircomCircuit<E>: Send + Sync,
+{
+    let witness_files = collect_witness_files(&opts.witnesses)?;
+    log::info!("Found {} witness files from {}", witness_files.len(), opts.witnesses);
+    let stems = batch_output_dirs(&witness_files)?;
+
+    let circuit_file = resolve_circuit_file(opts.circuit);
+    log::info!("Loading circuit from {}...", circuit_file);
+    let (r1cs, wire_mapping) = reader::load_r1cs::<E>(&circuit_file)?;
+    let circuit = CircomCircuit {
+        r1cs,
+        witness: None,
+        wire_mapping,
+        aux_offset: plonk::AUX_OFFSET,
+        symbols: load_symbols(&circuit_file, opts.sym)?,
+    };
+
+    let setup = prepare_setup(&circuit, opts.proving_key, &opts.srs_monomial_form, opts.srs_lagrange_form)?;
+
+    let setup = Arc::new(setup);
+    let circuit = Arc::new(circuit);
+    let output_root = PathBuf::from(&opts.output_dir);
+    let queue = Arc::new(Mutex::new(witness_files.into_iter().zip(stems).enumerate()));
+    let (tx, rx) = mpsc::channel();
+    let workers = (0..std::cmp::max(opts.jobs, 1))
+        .map(|_| {
+            let (setup, circuit, output_root, queue, tx) = (setup.clone(), circuit.clone(), output_root.clone(), queue.clone(), tx.clone());
+            std::thread::spawn(move || loop {
+                let next = queue.lock().unwrap().next();
+                let (i, (witness_file, stem)) = match next {
+                    Some(task) => task,
+                    None => break,
+                };
+                let output_dir = output_root.join(&stem);
+                log::info!("Proving {}...", witness_file.display());
+                let start = std::time::Instant::now();
+                // a bad witness may still panic deep inside synthesis, it must not take the whole batch down
+                let ret = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    prove_witness(&setup, &circuit, &witness_file, &output_dir)
+                }))
+                .unwrap_or_else(|panic| {
+                    let msg = match panic.downcast_ref::<&str>() {
+                        Some(s) => s.to_string(),
+                        None => panic
+                            .downcast_ref::<String>()
+                            .cloned()
+                            .unwrap_or_else(|| "prover panicked".to_owned()),
+                    };
+                    Err(anyhow::format_err!(msg))
+                });
+                let mut entry = BatchEntry {
+                    witness: witness_file.to_string_lossy().into_owned(),
+                    is_valid: ret.is_ok(),
+                    error_msg: String::new(),
+                    output_dir: String::new(),
+                    time_cost_secs: start.elapsed().as_secs_f64(),
+                };
+                match ret {
+                    Ok(_) => entry.output_dir = output_dir.to_string_lossy().into_owned(),
+                    Err(e) => {
+                        log::error!("Proving {} failed: {}", witness_file.display(), e);
+                        entry.error_msg = e.to_string();
+                    }
+                }
+                tx.send((i, entry)).expect("send batch result err");
+            })
+        })
+        .collect_vec();
+    drop(tx);
+
+    let mut results = rx.iter().collect_vec();
+    for worker in workers {
+        worker.join().expect("prover thread err");
+    }
+    results.sort_by_key(|(i, _)| *i);
+    let entries = results.into_iter().map(|(_, entry)| entry).collect_vec();
+
+    let num_proven = entries.iter().filter(|e| e.is_valid).count();
+    let manifest = BatchManifest {
+        num_witnesses: entries.len(),
+        num_proven,
+        num_failed: entries.len() - num_proven,
+        entries,
+    };
+    std::fs::create_dir_all(&output_root)?;
+    let manifest_file = output_root.join("manifest.json");
+    let writer = File::create(&manifest_file)?;
+    serde_json::to_writer_pretty(writer, &manifest)?;
+    log::info!(
+        "{} of {} witnesses proven, manifest saved to {}",
+        manifest.num_proven,
+        manifest.num_witnesses,
+        manifest_file.display()
+    );
+    Ok(())
 }
 
-fn verify(opts: VerifyOpts) {
-    let vk = reader::load_verification_key::<Bn256>(&opts.vk);
-    let proof = reader::load_proof::<Bn256>(&opts.proof);
-    let correct = plonk::verify(&vk, &proof).unwrap();
+fn verify<E: Engine>(opts: VerifyOpts) -> Result<(), anyhow::Error> {
+    let vk = reader::load_verification_key::<E>(&opts.vk)?;
+    let proof = match (opts.proofjson, opts.publicjson) {
+        (Some(proofjson), Some(publicjson)) => {
+            // the solidity encoding only exists for bn256
+            encoding::convert_proof::<Bn256, E>(&reader::load_proof_from_json_files(&proofjson, &publicjson, vk.n)?)?
+        }
+        _ => reader::load_proof::<E>(&opts.proof)?,
+    };
+    let correct = plonk::verify(&vk, &proof)?;
     if correct {
         log::info!("Proof is valid.");
     } else {
         log::info!("Proof is invalid!");
         std::process::exit(400);
     }
+    Ok(())
 }
 
-fn generate_verifier(opts: GenerateVerifierOpts) {
+fn generate_verifier(opts: GenerateVerifierOpts) -> Result<(), anyhow::Error> {
     cfg_if::cfg_if! {
         if #[cfg(feature = "solidity")] {
-            let vk = reader::load_verification_key::<Bn256>(&opts.vk);
+            let vk = reader::load_verification_key::<Bn256>(&opts.vk)?;
             bellman_vk_codegen::render_verification_key_from_default_template(&vk, &opts.sol);
             log::info!("Contract saved to {}", opts.sol);
+            Ok(())
         } else {
             unimplemented!("you must enable `solidity` feature flag");
         }
     }
 }
 
-fn export_vk(opts: ExportVerificationKeyOpts) {
+fn export_vk<E: Engine>(opts: ExportVerificationKeyOpts) -> Result<(), anyhow::Error> {
     let circuit_file = resolve_circuit_file(opts.circuit);
     log::info!("Loading circuit from {}...", circuit_file);
+    let (r1cs, _) = reader::load_r1cs::<E>(&circuit_file)?;
     let circuit = CircomCircuit {
-        r1cs: reader::load_r1cs(&circuit_file),
+        r1cs,
         witness: None,
         wire_mapping: None,
         aux_offset: plonk::AUX_OFFSET,
+        symbols: None,
     };
 
-    let setup = plonk::SetupForProver::prepare_setup_for_prover(circuit, reader::load_key_monomial_form(&opts.srs_monomial_form), None)
-        .expect("prepare err");
-    let vk = setup.make_verification_key().unwrap();
+    let setup = prepare_setup(&circuit, opts.proving_key, &opts.srs_monomial_form, None)?;
+    let vk = setup.make_verification_key()?;
 
     //let path = Path::new(&opts.vk);
     //assert!(!path.exists(), "path for saving verification key exists: {}", path.display());
-    let writer = File::create(&opts.vk).unwrap();
-    vk.write(writer).unwrap();
+    let writer = File::create(&opts.vk)?;
+    if opts.vk.ends_with(".json") {
+        encoding::write_verification_key_json(&vk, BufWriter::new(writer))?;
+    } else {
+        vk.write(writer)?;
+    }
     log::info!("Verification key saved to {}", opts.vk);
+    Ok(())
+}
+
+fn export_pk<E: Engine>(opts: ExportProvingKeyOpts) -> Result<(), anyhow::Error> {
+    let circuit_file = resolve_circuit_file(opts.circuit);
+    log::info!("Loading circuit from {}...", circuit_file);
+    let (r1cs, _) = reader::load_r1cs::<E>(&circuit_file)?;
+    let circuit = CircomCircuit {
+        r1cs,
+        witness: None,
+        wire_mapping: None,
+        aux_offset: plonk::AUX_OFFSET,
+        symbols: None,
+    };
+
+    let writer = BufWriter::new(File::create(&opts.proving_key)?);
+    plonk::write_proving_key(circuit, writer)?;
+    log::info!("Proving key saved to {}", opts.proving_key);
+    Ok(())
+}
+
+#[test]
+fn save_json_proof() {
+    use bellman_ce::kate_commitment::{Crs, CrsForMonomialForm};
+    use bellman_ce::pairing::{bn256::Fr, ff::PrimeField};
+
+    // out <== a * b, with a = 3 and b = 4
+    let circuit = br#"{"constraints": [[{"2": "1"}, {"3": "1"}, {"1": "1"}]], "nPubInputs": 0, "nOutputs": 1, "nVars": 4}"#;
+    let (r1cs, wire_mapping) = reader::load_r1cs_from_array::<Bn256>(circuit).unwrap();
+    let prover = plonkit::Prover::builder()
+        .circuit(r1cs, wire_mapping)
+        .srs_monomial(Crs::<Bn256, CrsForMonomialForm>::dummy_crs(256))
+        .build()
+        .unwrap();
+    let witness = ["1", "12", "3", "4"].iter().map(|w| Fr::from_str(w).unwrap()).collect();
+    let proof = prover.prove(witness).unwrap().proof;
+
+    let dir = std::env::temp_dir().join(format!("plonkit-save-json-proof-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let proof_file = dir.join("proof.json").to_str().unwrap().to_owned();
+    let public_file = dir.join("public.json").to_str().unwrap().to_owned();
+    // `prove -p proof.json` with the default --proofjson
+    save_proof(&proof, &proof_file, &proof_file, &public_file).unwrap();
+
+    let json: encoding::ProofJson = serde_json::from_reader(File::open(&proof_file).unwrap()).unwrap();
+    let (mut expected, mut actual) = (Vec::new(), Vec::new());
+    proof.write(&mut expected).unwrap();
+    encoding::proof_from_json::<Bn256>(&json).unwrap().write(&mut actual).unwrap();
+    assert_eq!(actual, expected);
+    std::fs::remove_dir_all(&dir).unwrap();
 }
@@ -0,0 +1,131 @@
+// Node.js addon, built with `cargo build --release --features node` and loaded from the shared library
+// renamed to plonkit.node. Only bn256 is supported, like the solidity verifier.
+//
+//   const { Prover } = require('./plonkit.node');
+//   const prover = new Prover('circuit.r1cs', 'setup_2^20.key');
+//   const { proof, inputs } = await prover.prove(fs.readFileSync('witness.wtns'));
+//   prover.verify(proof, inputs);
+use bellman_ce::pairing::bn256::Bn256;
+use napi::{CallContext, Env, JsBoolean, JsBuffer, JsObject, JsString, JsUndefined, Property, Task};
+use std::sync::Arc;
+
+use crate::api::{ProofArtifacts, Prover, Verifier};
+use crate::reader;
+
+struct NodeProver {
+    prover: Arc<Prover<Bn256>>,
+    verifier: Verifier<Bn256>,
+}
+
+struct ProveTask {
+    prover: Arc<Prover<Bn256>>,
+    witness: Vec<u8>,
+}
+
+fn to_napi_error(e: crate::Error) -> napi::Error {
+    napi::Error::from_reason(e.to_string())
+}
+
+fn to_string_array(env: &Env, values: &[String]) -> napi::Result<JsObject> {
+    let mut array = env.create_array_with_length(values.len())?;
+    for (i, value) in values.iter().enumerate() {
+        array.set_element(i as u32, env.create_string(value)?)?;
+    }
+    Ok(array)
+}
+
+fn from_string_array(array: JsObject) -> napi::Result<Vec<String>> {
+    (0..array.get_array_length()?)
+        .map(|i| array.get_element::<JsString>(i)?.into_utf8()?.into_owned())
+        .collect()
+}
+
+impl Task for ProveTask {
+    type Output = ProofArtifacts<Bn256>;
+    type JsValue = JsObject;
+
+    // runs on the libuv thread pool, not on the event loop
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        let witness = reader::load_witness_from_array::<Bn256>(std::mem::take(&mut self.witness)).map_err(to_napi_error)?;
+        self.prover.prove(witness).map_err(to_napi_error)
+    }
+
+    fn resolve(self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        let mut result = env.create_object()?;
+        result.set_named_property("proof", to_string_array(&env, &output.serialized_proof.unwrap_or_default())?)?;
+        result.set_named_property("inputs", to_string_array(&env, &output.public_inputs)?)?;
+        Ok(result)
+    }
+}
+
+/// `new Prover(circuit, srsMonomialForm, srsLagrangeForm?)` loads the files and transpiles the circuit.
+#[js_function(3)]
+fn prover_constructor(ctx: CallContext) -> napi::Result<JsUndefined> {
+    let circuit = ctx.get::<JsString>(0)?.into_utf8()?.into_owned()?;
+    let srs_monomial_form = ctx.get::<JsString>(1)?.into_utf8()?.into_owned()?;
+    let srs_lagrange_form = match ctx.length {
+        3 => Some(ctx.get::<JsString>(2)?.into_utf8()?.into_owned()?),
+        _ => None,
+    };
+
+    let (r1cs, wire_mapping) = reader::load_r1cs::<Bn256>(&circuit).map_err(to_napi_error)?;
+    let mut builder = Prover::builder()
+        .circuit(r1cs, wire_mapping)
+        .srs_monomial(reader::load_key_monomial_form(&srs_monomial_form).map_err(to_napi_error)?);
+    if let Some(key_lagrange_form) = reader::maybe_load_key_lagrange_form(srs_lagrange_form).map_err(to_napi_error)? {
+        builder = builder.srs_lagrange(key_lagrange_form);
+    }
+    let prover = builder.build().map_err(to_napi_error)?;
+    let verifier = Verifier::from_vk(prover.verification_key().map_err(to_napi_error)?);
+
+    let mut this: JsObject = ctx.this_unchecked();
+    ctx.env.wrap(
+        &mut this,
+        NodeProver {
+            prover: Arc::new(prover),
+            verifier,
+        },
+    )?;
+    ctx.env.get_undefined()
+}
+
+/// `prover.prove(witness)` takes the contents of a .wtns file and resolves to `{ proof, inputs }`,
+/// the string arrays of proof.json and public.json.
+#[js_function(1)]
+fn prove(ctx: CallContext) -> napi::Result<JsObject> {
+    let witness = ctx.get::<JsBuffer>(0)?.into_value()?.to_vec();
+    let this: JsObject = ctx.this_unchecked();
+    let node_prover: &mut NodeProver = ctx.env.unwrap(&this)?;
+    let task = ProveTask {
+        prover: node_prover.prover.clone(),
+        witness,
+    };
+    Ok(ctx.env.spawn(task)?.promise_object())
+}
+
+/// `prover.verify(proof, inputs)` checks the string arrays returned by `prove` against the circuit.
+#[js_function(2)]
+fn verify(ctx: CallContext) -> napi::Result<JsBoolean> {
+    let proof = from_string_array(ctx.get::<JsObject>(0)?)?;
+    let inputs = from_string_array(ctx.get::<JsObject>(1)?)?;
+    let this: JsObject = ctx.this_unchecked();
+    let node_prover: &mut NodeProver = ctx.env.unwrap(&this)?;
+    let vk = node_prover.verifier.verification_key();
+    let proof = reader::load_proof_from_serialized(&inputs, &proof, vk.n).map_err(to_napi_error)?;
+    let correct = node_prover.verifier.verify(&proof).map_err(to_napi_error)?;
+    ctx.env.get_boolean(correct)
+}
+
+#[module_exports]
+fn init(mut exports: JsObject, env: Env) -> napi::Result<()> {
+    let prover_class = env.define_class(
+        "Prover",
+        prover_constructor,
+        &[
+            Property::new(&env, "prove")?.with_method(prove),
+            Property::new(&env, "verify")?.with_method(verify),
+        ],
+    )?;
+    exports.set_named_property("Prover", prover_class)?;
+    Ok(())
+}
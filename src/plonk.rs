@@ -1,30 +1,42 @@
 // Most of this file is forked from source codes of [Matter Labs's zkSync](https://github.com/matter-labs/zksync)
-use crate::circom_circuit::CircomCircuit;
+use crate::circom_circuit::{constraint_wires, CircomCircuit, R1CS};
+use crate::error::{Error, Result};
+use crate::sym_file::{common_component, component_ancestors, component_of, SymbolTable};
 use crate::transpile::{transpile_with_gates_count, ConstraintStat, TranspilerWrapper};
 use bellman_ce::{
     kate_commitment::{Crs, CrsForLagrangeForm, CrsForMonomialForm},
     pairing::Engine,
     plonk::{
-        better_cs::adaptor::TranspilationVariant,
+        better_cs::adaptor::{read_transpilation_hints, write_transpilation_hints, TranspilationVariant},
         better_cs::cs::PlonkCsWidth4WithNextStepParams,
         better_cs::keys::{Proof, SetupPolynomials, VerificationKey},
         commitments::transcript::keccak_transcript::RollingKeccakTranscript,
         is_satisfied_using_one_shot_check, make_verification_key, prove, prove_by_steps, setup,
     },
     worker::Worker,
-    Circuit, ScalarEngine, SynthesisError,
+    Circuit, ScalarEngine,
 };
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use itertools::Itertools;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
 
 pub const AUX_OFFSET: usize = 1;
 
 const SETUP_MIN_POW2: u32 = 20;
-const SETUP_MAX_POW2: u32 = 26;
+pub const SETUP_MAX_POW2: u32 = 26;
 
-pub fn gen_key_monomial_form<E: Engine>(power: u32) -> Result<Crs<E, CrsForMonomialForm>, anyhow::Error> {
-    anyhow::ensure!(
-        (SETUP_MIN_POW2..=SETUP_MAX_POW2).contains(&power),
-        "setup power of two is not in the correct range"
-    );
+// "pkey" followed by the format version
+const PROVING_KEY_MAGIC: [u8; 4] = *b"pkey";
+const PROVING_KEY_VERSION: u32 = 1;
+
+pub fn gen_key_monomial_form<E: Engine>(power: u32) -> Result<Crs<E, CrsForMonomialForm>> {
+    if !(SETUP_MIN_POW2..=SETUP_MAX_POW2).contains(&power) {
+        return Err(Error::Format(format!(
+            "setup power of two {} is not in the range {}..={}",
+            power, SETUP_MIN_POW2, SETUP_MAX_POW2
+        )));
+    }
 
     Ok(Crs::<E, CrsForMonomialForm>::crs_42(1 << power, &Worker::new()))
 }
@@ -36,6 +48,65 @@ pub struct SetupForProver<E: Engine> {
     key_lagrange_form: Option<Crs<E, CrsForLagrangeForm>>,
 }
 
+/// Cost of a circom component. `num_*` include all subcomponents, `self_*` don't.
+#[derive(serde::Serialize, Clone, Default)]
+pub struct ComponentStat {
+    pub path: String,
+    pub num_gates: usize,
+    pub num_constraints: usize,
+    pub num_variables: usize,
+    pub self_gates: usize,
+    pub self_constraints: usize,
+    pub self_variables: usize,
+}
+
+// 7 selectors (q_a..q_d, q_m, q_const, q_d_next) and 4 permutation polynomials
+const NUM_SETUP_POLYNOMIALS: usize = 11;
+// the quotient is computed on a coset 4 times the domain size, over the setup polynomials,
+// 4 witness polynomials and the grand product
+const QUOTIENT_LDE_FACTOR: usize = 4;
+const NUM_LDE_POLYNOMIALS: usize = NUM_SETUP_POLYNOMIALS + 5;
+// circuits with less free rows than this percentage of the domain are flagged
+const NEAR_BOUNDARY_PERCENT: usize = 5;
+
+/// Rough proving requirements derived from the gate count.
+#[derive(serde::Serialize)]
+pub struct ResourceEstimate {
+    /// Size of the evaluation domain, the padded gate count
+    pub domain_size: usize,
+    /// Smallest power of two of a monomial SRS that fits the circuit
+    pub min_srs_power: u32,
+    pub srs_monomial_form_bytes: usize,
+    pub srs_lagrange_form_bytes: usize,
+    /// Peak prover memory with the monomial SRS only
+    pub prover_memory_bytes: usize,
+    /// Peak prover memory with both the monomial and the lagrange SRS
+    pub prover_memory_bytes_with_lagrange: usize,
+    /// Gates that can be added before the domain doubles
+    pub free_gates: usize,
+    pub near_domain_boundary: bool,
+}
+
+pub fn estimate_resources<E: Engine>(num_gates: usize) -> ResourceEstimate {
+    // the assembly is padded so that `n + 1` is a power of two, see `setup_polynomials.n`
+    let domain_size = (num_gates + 1).next_power_of_two();
+    let fr_size = std::mem::size_of::<E::Fr>();
+    let srs_size = domain_size * std::mem::size_of::<E::G1Affine>();
+    let prover_memory_bytes =
+        srs_size + domain_size * fr_size * NUM_SETUP_POLYNOMIALS + QUOTIENT_LDE_FACTOR * domain_size * fr_size * NUM_LDE_POLYNOMIALS;
+    let free_gates = domain_size - (num_gates + 1);
+    ResourceEstimate {
+        domain_size,
+        min_srs_power: domain_size.trailing_zeros(),
+        srs_monomial_form_bytes: srs_size,
+        srs_lagrange_form_bytes: srs_size,
+        prover_memory_bytes,
+        prover_memory_bytes_with_lagrange: prover_memory_bytes + srs_size,
+        free_gates,
+        near_domain_boundary: free_gates * 100 < domain_size * NEAR_BOUNDARY_PERCENT,
+    }
+}
+
 #[derive(serde::Serialize)]
 pub struct AnalyseResult {
     pub num_inputs: usize,
@@ -45,11 +116,75 @@ pub struct AnalyseResult {
     pub num_nontrivial_constraints: usize,
     pub num_gates: usize,
     pub num_hints: usize,
+    pub resources: ResourceEstimate,
+    /// Number of constraints per kind of transpilation, e.g. `MergeLinearCombinations`
+    pub variant_kinds: BTreeMap<String, usize>,
+    /// Number of constraints per transpilation variant, including how each linear combination is transpiled
+    pub variants: BTreeMap<String, usize>,
+    pub top_constraints_by_gates: Vec<ConstraintStat>,
+    pub top_constraints_by_lc_width: Vec<ConstraintStat>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub constraint_stats: Vec<ConstraintStat>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub component_stats: Vec<ComponentStat>,
+}
+
+impl AnalyseResult {
+    /// Gates per component in the folded stack format taken by flamegraph tools.
+    pub fn folded_stacks(&self) -> String {
+        self.component_stats
+            .iter()
+            .filter(|c| c.self_gates > 0)
+            .map(|c| format!("{} {}\n", c.path.replace('.', ";"), c.self_gates))
+            .collect()
+    }
+}
+
+fn component_entry<'a>(components: &'a mut BTreeMap<String, ComponentStat>, path: &str) -> &'a mut ComponentStat {
+    components.entry(path.to_owned()).or_insert_with(|| ComponentStat {
+        path: path.to_owned(),
+        ..Default::default()
+    })
 }
 
-pub fn analyse<E: Engine>(circuit: CircomCircuit<E>) -> Result<AnalyseResult, anyhow::Error> {
+/// Rolls the stats of each constraint and wire up to the circom component they belong to and its ancestors.
+/// A constraint belongs to the innermost component containing all of its signals.
+fn component_stats<E: Engine>(
+    circuit: &CircomCircuit<E>,
+    symbols: &SymbolTable,
+    constraint_stats: &[ConstraintStat],
+) -> Vec<ComponentStat> {
+    let mut components = BTreeMap::<String, ComponentStat>::new();
+    for stat in constraint_stats {
+        let constraint = match stat.name.parse::<usize>().ok().and_then(|i| circuit.r1cs.constraints.get(i)) {
+            Some(constraint) => constraint,
+            None => continue,
+        };
+        let wires = constraint_wires::<E>(constraint);
+        let path = common_component(wires.iter().filter_map(|wire| symbols.wire_name(*wire))).unwrap_or_else(|| "<unknown>".to_owned());
+        let component = component_entry(&mut components, &path);
+        component.self_gates += stat.num_gates;
+        component.self_constraints += 1;
+        for ancestor in component_ancestors(&path) {
+            let component = component_entry(&mut components, ancestor);
+            component.num_gates += stat.num_gates;
+            component.num_constraints += 1;
+        }
+    }
+    for (_, signal) in symbols.wires() {
+        let path = component_of(signal);
+        component_entry(&mut components, path).self_variables += 1;
+        for ancestor in component_ancestors(path) {
+            component_entry(&mut components, ancestor).num_variables += 1;
+        }
+    }
+    let mut components = components.into_iter().map(|(_, component)| component).collect_vec();
+    components.sort_by(|a, b| b.num_gates.cmp(&a.num_gates).then_with(|| a.path.cmp(&b.path)));
+    components
+}
+
+/// `top_n` is the number of most expensive constraints listed by gates and by linear combination width.
+pub fn analyse<E: Engine>(circuit: CircomCircuit<E>, top_n: usize) -> Result<AnalyseResult> {
     let mut transpiler = TranspilerWrapper::<E, PlonkCsWidth4WithNextStepParams>::new();
     let mut result = AnalyseResult {
         num_inputs: circuit.r1cs.num_inputs,
@@ -59,35 +194,125 @@ pub fn analyse<E: Engine>(circuit: CircomCircuit<E>) -> Result<AnalyseResult, an
         num_nontrivial_constraints: 0,
         num_gates: 0,
         num_hints: 0,
+        resources: estimate_resources::<E>(0),
+        variant_kinds: BTreeMap::new(),
+        variants: BTreeMap::new(),
+        top_constraints_by_gates: Vec::new(),
+        top_constraints_by_lc_width: Vec::new(),
         constraint_stats: Vec::new(),
+        component_stats: Vec::new(),
     };
-    circuit
-        .synthesize(&mut transpiler)
-        .expect("sythesize into traspilation must succeed");
+    circuit.clone().synthesize(&mut transpiler)?;
     result.num_nontrivial_constraints = transpiler.constraint_stats.len();
     result.num_gates = transpiler.num_gates();
+    result.resources = estimate_resources::<E>(result.num_gates);
     result.constraint_stats = transpiler.constraint_stats.clone();
+    if let Some(symbols) = &circuit.symbols {
+        for stat in result.constraint_stats.iter_mut() {
+            // constraints are named after their index in the r1cs, see `CircomCircuit::synthesize`
+            if let Some(constraint) = stat.name.parse::<usize>().ok().and_then(|i| circuit.r1cs.constraints.get(i)) {
+                stat.signals = circuit.signal_names(&constraint_wires::<E>(constraint));
+            }
+        }
+        result.component_stats = component_stats(&circuit, symbols, &result.constraint_stats);
+    }
     let hints = transpiler.into_hints();
     result.num_hints = hints.len();
+    for (index, variant) in hints.iter() {
+        let variant = format!("{:?}", variant);
+        let kind = variant.split('(').next().unwrap_or_default().to_owned();
+        *result.variant_kinds.entry(kind).or_insert(0) += 1;
+        *result.variants.entry(variant.clone()).or_insert(0) += 1;
+        // hints are indexed by the order of `enforce` calls, the same order as the stats
+        if let Some(stat) = result.constraint_stats.get_mut(*index) {
+            stat.variant = variant;
+        }
+    }
+    let top_by = |key: fn(&ConstraintStat) -> usize| {
+        let mut stats = result.constraint_stats.iter().collect_vec();
+        stats.sort_by(|a, b| key(b).cmp(&key(a)));
+        stats.into_iter().take(top_n).cloned().collect_vec()
+    };
+    let top_constraints_by_gates = top_by(|stat| stat.num_gates);
+    let top_constraints_by_lc_width = top_by(|stat| stat.lc_width);
+    result.top_constraints_by_gates = top_constraints_by_gates;
+    result.top_constraints_by_lc_width = top_constraints_by_lc_width;
     Ok(result)
 }
 
+type SetupAndHints<E> = (
+    SetupPolynomials<E, PlonkCsWidth4WithNextStepParams>,
+    Vec<(usize, TranspilationVariant)>,
+);
+
+fn transpile_and_setup<E: Engine, C: Circuit<E> + Clone>(circuit: C) -> Result<SetupAndHints<E>> {
+    let (gates_count, hints) = transpile_with_gates_count(circuit.clone())?;
+    log::info!("transpile done, gates_count {} hints size {}", gates_count, hints.len());
+    let setup_polynomials = setup(circuit, &hints)?;
+    check_setup_size(setup_polynomials.n)?;
+    Ok((setup_polynomials, hints))
+}
+
+fn check_setup_size(n: usize) -> Result<()> {
+    let size = n.next_power_of_two().trailing_zeros();
+    log::info!("circuit setup_polynomials.n {:?} size {}", n, size);
+    let setup_power_of_two = std::cmp::max(size, SETUP_MIN_POW2); // for exit circuit
+    if setup_power_of_two > SETUP_MAX_POW2 {
+        return Err(Error::SrsTooSmall {
+            required: 1 << setup_power_of_two,
+            available: 1 << SETUP_MAX_POW2,
+        });
+    }
+    Ok(())
+}
+
+// The monomial srs must hold at least as many powers as the domain, the lagrange one exactly as many
+fn check_srs_size<E: Engine>(
+    n: usize,
+    key_monomial_form: &Crs<E, CrsForMonomialForm>,
+    key_lagrange_form: &Option<Crs<E, CrsForLagrangeForm>>,
+) -> Result<()> {
+    let required = n.next_power_of_two();
+    let available = key_monomial_form.g1_bases.len();
+    if available < required {
+        return Err(Error::SrsTooSmall { required, available });
+    }
+    if let Some(key_lagrange_form) = key_lagrange_form {
+        let available = key_lagrange_form.g1_bases.len();
+        if available < required {
+            return Err(Error::SrsTooSmall { required, available });
+        }
+        if available != required {
+            return Err(Error::Format(format!(
+                "the lagrange form srs is for a domain of {} instead of {}",
+                available, required
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Transpiles and sets up the circuit, then writes the result as a proving key that
+/// `SetupForProver::load` can read back without redoing either step.
+pub fn write_proving_key<E: Engine, W: Write>(circuit: CircomCircuit<E>, mut writer: W) -> Result<()> {
+    let r1cs_hash = circuit.r1cs.hash();
+    let (setup_polynomials, hints) = transpile_and_setup(circuit)?;
+    writer.write_all(&PROVING_KEY_MAGIC)?;
+    writer.write_u32::<LittleEndian>(PROVING_KEY_VERSION)?;
+    writer.write_all(&r1cs_hash)?;
+    setup_polynomials.write(&mut writer)?;
+    write_transpilation_hints(&hints, &mut writer)?;
+    Ok(())
+}
+
 impl<E: Engine> SetupForProver<E> {
     pub fn prepare_setup_for_prover<C: Circuit<E> + Clone>(
         circuit: C,
         key_monomial_form: Crs<E, CrsForMonomialForm>,
         key_lagrange_form: Option<Crs<E, CrsForLagrangeForm>>,
-    ) -> Result<Self, anyhow::Error> {
-        let (gates_count, hints) = transpile_with_gates_count(circuit.clone())?;
-        log::info!("transpile done, gates_count {} hints size {}", gates_count, hints.len());
-        let setup_polynomials = setup(circuit, &hints)?;
-        let size = setup_polynomials.n.next_power_of_two().trailing_zeros();
-        log::info!("circuit setup_polynomials.n {:?} size {}", setup_polynomials.n, size);
-        let setup_power_of_two = std::cmp::max(size, SETUP_MIN_POW2); // for exit circuit
-        anyhow::ensure!(
-            (SETUP_MIN_POW2..=SETUP_MAX_POW2).contains(&setup_power_of_two),
-            "setup power of two is not in the correct range"
-        );
+    ) -> Result<Self> {
+        let (setup_polynomials, hints) = transpile_and_setup(circuit)?;
+        check_srs_size(setup_polynomials.n, &key_monomial_form, &key_lagrange_form)?;
 
         Ok(SetupForProver {
             setup_polynomials,
@@ -97,17 +322,59 @@ impl<E: Engine> SetupForProver<E> {
         })
     }
 
-    pub fn make_verification_key(&self) -> Result<VerificationKey<E, PlonkCsWidth4WithNextStepParams>, SynthesisError> {
-        make_verification_key(&self.setup_polynomials, &self.key_monomial_form)
+    /// Builds the setup from a proving key written by `write_proving_key`.
+    /// The key must have been exported from the same `r1cs`.
+    pub fn load<R: Read>(
+        mut reader: R,
+        r1cs: &R1CS<E>,
+        key_monomial_form: Crs<E, CrsForMonomialForm>,
+        key_lagrange_form: Option<Crs<E, CrsForLagrangeForm>>,
+    ) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != PROVING_KEY_MAGIC {
+            return Err(Error::Format("invalid proving key header".to_owned()));
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != PROVING_KEY_VERSION {
+            return Err(Error::Format(format!("unsupported proving key version {}", version)));
+        }
+        let mut r1cs_hash = [0u8; 32];
+        reader.read_exact(&mut r1cs_hash)?;
+        if r1cs_hash != r1cs.hash() {
+            return Err(Error::Format("proving key does not match the circuit".to_owned()));
+        }
+
+        let setup_polynomials = SetupPolynomials::<E, PlonkCsWidth4WithNextStepParams>::read(&mut reader)?;
+        let hints = read_transpilation_hints(&mut reader)?;
+        log::info!("proving key loaded, hints size {}", hints.len());
+        check_setup_size(setup_polynomials.n)?;
+        check_srs_size(setup_polynomials.n, &key_monomial_form, &key_lagrange_form)?;
+
+        Ok(SetupForProver {
+            setup_polynomials,
+            hints,
+            key_monomial_form,
+            key_lagrange_form,
+        })
     }
 
-    pub fn validate_witness<C: Circuit<E> + Clone>(&self, circuit: C) -> Result<(), SynthesisError> {
-        is_satisfied_using_one_shot_check(circuit, &self.hints)
+    pub fn make_verification_key(&self) -> Result<VerificationKey<E, PlonkCsWidth4WithNextStepParams>> {
+        Ok(make_verification_key(&self.setup_polynomials, &self.key_monomial_form)?)
     }
 
-    pub fn prove<C: Circuit<E> + Clone>(&self, circuit: C) -> Result<Proof<E, PlonkCsWidth4WithNextStepParams>, SynthesisError> {
-        is_satisfied_using_one_shot_check(circuit.clone(), &self.hints).expect("must satisfy");
-        match &self.key_lagrange_form {
+    pub fn validate_witness(&self, circuit: CircomCircuit<E>) -> Result<()> {
+        // evaluating the r1cs directly tells which constraint fails, the transpiled check can't
+        if let Some(constraint) = circuit.find_unsatisfied_constraint()? {
+            return Err(Error::UnsatisfiedConstraint(constraint));
+        }
+        is_satisfied_using_one_shot_check(circuit, &self.hints)?;
+        Ok(())
+    }
+
+    pub fn prove(&self, circuit: CircomCircuit<E>) -> Result<Proof<E, PlonkCsWidth4WithNextStepParams>> {
+        self.validate_witness(circuit.clone())?;
+        let proof = match &self.key_lagrange_form {
             Some(key_lagrange_form) => prove::<_, _, RollingKeccakTranscript<<E as ScalarEngine>::Fr>>(
                 circuit,
                 &self.hints,
@@ -123,7 +390,8 @@ impl<E: Engine> SetupForProver<E> {
                 &self.key_monomial_form,
                 None,
             ),
-        }
+        }?;
+        Ok(proof)
     }
 
     pub fn get_srs_lagrange_form_from_monomial_form(&self) -> Crs<E, CrsForLagrangeForm> {
@@ -138,6 +406,112 @@ impl<E: Engine> SetupForProver<E> {
 pub fn verify<E: Engine>(
     vk: &VerificationKey<E, PlonkCsWidth4WithNextStepParams>,
     proof: &Proof<E, PlonkCsWidth4WithNextStepParams>,
-) -> Result<bool, SynthesisError> {
-    bellman_ce::plonk::verify::<_, RollingKeccakTranscript<<E as ScalarEngine>::Fr>>(&proof, &vk)
+) -> Result<bool> {
+    Ok(bellman_ce::plonk::verify::<_, RollingKeccakTranscript<<E as ScalarEngine>::Fr>>(
+        &proof, &vk,
+    )?)
+}
+
+// main.out = main.a * main.b, main.sq.out = main.sq.in^2 and main.out = main.a + main.b + main.sq.in
+#[cfg(test)]
+fn sample_circuit() -> CircomCircuit<bellman_ce::pairing::bn256::Bn256> {
+    use bellman_ce::pairing::bn256::Fr;
+    use bellman_ce::pairing::ff::PrimeField;
+
+    let fr = |s: &str| Fr::from_str(s).unwrap();
+    let sym = "1,1,0,main.out\n2,2,0,main.a\n3,3,0,main.b\n4,4,1,main.sq.in\n5,5,1,main.sq.out\n";
+    CircomCircuit {
+        r1cs: R1CS {
+            num_inputs: 2,
+            num_aux: 4,
+            num_variables: 6,
+            constraints: vec![
+                (vec![(2, fr("1"))], vec![(3, fr("1"))], vec![(1, fr("1"))]),
+                (vec![(4, fr("1"))], vec![(4, fr("1"))], vec![(5, fr("1"))]),
+                (
+                    vec![(0, fr("1"))],
+                    vec![(2, fr("1")), (3, fr("1")), (4, fr("1"))],
+                    vec![(1, fr("1"))],
+                ),
+            ],
+        },
+        witness: None,
+        wire_mapping: None,
+        aux_offset: AUX_OFFSET,
+        symbols: Some(std::sync::Arc::new(crate::sym_file::from_reader(sym.as_bytes()).unwrap())),
+    }
+}
+
+#[test]
+fn components() {
+    let result = analyse(sample_circuit(), 2).unwrap();
+    let paths = result.component_stats.iter().map(|c| c.path.as_str()).collect_vec();
+    assert_eq!(paths, vec!["main", "main.sq"]);
+
+    let (main, sq) = (&result.component_stats[0], &result.component_stats[1]);
+    assert_eq!((main.num_constraints, main.self_constraints), (3, 2));
+    assert_eq!((main.num_variables, main.self_variables), (5, 3));
+    assert_eq!((sq.num_constraints, sq.self_constraints), (1, 1));
+    assert_eq!((sq.num_variables, sq.self_variables), (2, 2));
+    assert_eq!(main.num_gates, main.self_gates + sq.num_gates);
+    assert_eq!(
+        main.num_gates,
+        result.constraint_stats.iter().map(|stat| stat.num_gates).sum::<usize>()
+    );
+
+    let folded = result.folded_stacks();
+    assert!(folded.contains(&format!("main {}\n", main.self_gates)));
+    assert!(folded.contains(&format!("main;sq {}\n", sq.self_gates)));
+}
+
+#[test]
+fn hotspots() {
+    let result = analyse(sample_circuit(), 2).unwrap();
+    assert_eq!(result.num_nontrivial_constraints, 3);
+    assert_eq!(result.variant_kinds.values().sum::<usize>(), result.num_hints);
+    assert_eq!(result.variants.values().sum::<usize>(), result.num_hints);
+
+    let widths = result
+        .top_constraints_by_lc_width
+        .iter()
+        .map(|stat| (stat.name.as_str(), stat.lc_width))
+        .collect_vec();
+    // ties keep the r1cs order
+    assert_eq!(widths, vec![("2", 5), ("0", 3)]);
+    assert_eq!(
+        result.top_constraints_by_lc_width[0].signals,
+        vec!["<wire 0>", "main.out", "main.a", "main.b", "main.sq.in"]
+    );
+
+    let gates = result.top_constraints_by_gates.iter().map(|stat| stat.num_gates).collect_vec();
+    assert_eq!(gates.len(), 2);
+    assert!(gates[0] >= gates[1]);
+    assert_eq!(Some(gates[0]), result.constraint_stats.iter().map(|stat| stat.num_gates).max());
+}
+
+#[test]
+fn resources_near_power_of_two() {
+    use bellman_ce::pairing::bn256::Bn256;
+
+    // the assembly needs one row more than the gates, 2^10 - 1 gates fill a domain of 2^10 exactly
+    let below = estimate_resources::<Bn256>((1 << 10) - 1);
+    assert_eq!((below.domain_size, below.min_srs_power, below.free_gates), (1 << 10, 10, 0));
+    assert!(below.near_domain_boundary);
+
+    let at = estimate_resources::<Bn256>(1 << 10);
+    assert_eq!((at.domain_size, at.min_srs_power, at.free_gates), (1 << 11, 11, (1 << 10) - 1));
+    assert!(!at.near_domain_boundary);
+    assert_eq!(at.srs_monomial_form_bytes, 2 * below.srs_monomial_form_bytes);
+    assert_eq!(at.prover_memory_bytes, 2 * below.prover_memory_bytes);
+
+    let above = estimate_resources::<Bn256>((1 << 10) + 1);
+    assert_eq!(
+        (above.domain_size, above.min_srs_power, above.free_gates),
+        (1 << 11, 11, (1 << 10) - 2)
+    );
+    assert!(!above.near_domain_boundary);
+
+    // 5% of 2^11 is 102.4 free gates
+    assert!(estimate_resources::<Bn256>((1 << 11) - 1 - 102).near_domain_boundary);
+    assert!(!estimate_resources::<Bn256>((1 << 11) - 1 - 103).near_domain_boundary);
 }
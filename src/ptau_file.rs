@@ -0,0 +1,288 @@
+// Powers of tau files of snarkjs (.ptau), as published by the Hermez and perpetual powers of tau ceremonies.
+// Only the tau powers are read: 2^power * 2 - 1 points in G1 and 2^power in G2, each coordinate stored as little
+// endian limbs in montgomery form. The ceremonies for plonk run over bn128, so bn256 is the only supported curve.
+use crate::error::{Error, Result};
+use bellman_ce::kate_commitment::{Crs, CrsForMonomialForm};
+use bellman_ce::pairing::{
+    bls12_381,
+    bn256::{Bn256, Fq, Fq2, G1Affine, G2Affine},
+    ff::{PrimeField, PrimeFieldRepr},
+    CurveAffine,
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom};
+
+pub struct Header {
+    pub n8: u32,
+    pub prime: Vec<u8>,
+    pub power: u32,
+    pub ceremony_power: u32,
+}
+
+const SECTION_HEADER: u32 = 1;
+const SECTION_TAU_G1: u32 = 2;
+const SECTION_TAU_G2: u32 = 3;
+
+// The largest ceremony published, 2^28 powers
+const MAX_POWER: u32 = 28;
+
+const G1_SIZE: u64 = 64;
+const G2_SIZE: u64 = 128;
+
+struct Section {
+    offset: u64,
+    size: u64,
+}
+
+fn base_field_prime<F: PrimeField>() -> Vec<u8> {
+    let mut prime = Vec::new();
+    F::char().write_le(&mut prime).expect("write to vec");
+    prime
+}
+
+// Locates all sections, the contributions and the alpha, beta and lagrange ones are skipped
+fn read_sections<R: Read + Seek>(mut reader: R) -> Result<BTreeMap<u32, Section>> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"ptau" {
+        return Err(Error::Format("Invalid magic number".to_owned()));
+    }
+
+    let version = reader.read_u32::<LittleEndian>()?;
+    if version != 1 {
+        return Err(Error::Format("Unsupported version".to_owned()));
+    }
+
+    let num_sections = reader.read_u32::<LittleEndian>()?;
+    let mut sections = BTreeMap::new();
+    for _ in 0..num_sections {
+        let sec_type = reader.read_u32::<LittleEndian>()?;
+        let sec_size = reader.read_u64::<LittleEndian>()?;
+        let offset = reader.seek(SeekFrom::Current(0))?;
+        if sections.insert(sec_type, Section { offset, size: sec_size }).is_some() {
+            return Err(Error::Format(format!("Duplicated section type {}", sec_type)));
+        }
+        reader.seek(SeekFrom::Start(offset + sec_size))?;
+    }
+    Ok(sections)
+}
+
+// Seeks to the beginning of a section after checking its size
+fn seek_section<R: Seek>(mut reader: R, sections: &BTreeMap<u32, Section>, sec_type: u32, name: &str, size: u64) -> Result<()> {
+    let section = sections
+        .get(&sec_type)
+        .ok_or_else(|| Error::Format(format!("Missing {} section", name)))?;
+    if section.size != size {
+        return Err(Error::Format(format!(
+            "Invalid {} section size: expected {} bytes, declared {} bytes",
+            name, size, section.size
+        )));
+    }
+    reader.seek(SeekFrom::Start(section.offset))?;
+    Ok(())
+}
+
+fn read_header<R: Read + Seek>(mut reader: R, sections: &BTreeMap<u32, Section>) -> Result<Header> {
+    let section = sections
+        .get(&SECTION_HEADER)
+        .ok_or_else(|| Error::Format("Missing header section".to_owned()))?;
+    reader.seek(SeekFrom::Start(section.offset))?;
+    let n8 = reader.read_u32::<LittleEndian>()?;
+    if section.size != 12 + n8 as u64 {
+        return Err(Error::Format("Invalid header section size".to_owned()));
+    }
+    let mut prime = vec![0u8; n8 as usize];
+    reader.read_exact(&mut prime)?;
+    let power = reader.read_u32::<LittleEndian>()?;
+    if power > MAX_POWER {
+        return Err(Error::Format(format!(
+            "Unsupported power {}, ptau files go up to {}",
+            power, MAX_POWER
+        )));
+    }
+    Ok(Header {
+        n8,
+        prime,
+        power,
+        ceremony_power: reader.read_u32::<LittleEndian>()?,
+    })
+}
+
+fn check_curve(header: &Header) -> Result<()> {
+    if header.prime == base_field_prime::<Fq>() {
+        Ok(())
+    } else if header.prime == base_field_prime::<bls12_381::Fq>() {
+        Err(Error::FieldMismatch(
+            "the ptau file is for bls12_381, only bn256 is supported".to_owned(),
+        ))
+    } else {
+        Err(Error::FieldMismatch("the ptau file is for an unsupported curve".to_owned()))
+    }
+}
+
+fn section_len(count: u64, point_size: u64) -> Result<u64> {
+    count
+        .checked_mul(point_size)
+        .ok_or_else(|| Error::Format(format!("the ptau file holds too many points: {}", count)))
+}
+
+fn read_fq<R: Read>(mut reader: R) -> Result<Fq> {
+    let mut repr = <Fq as PrimeField>::Repr::default();
+    repr.read_le(&mut reader)?;
+    Fq::from_raw_repr(repr).map_err(|e| Error::Format(e.to_string()))
+}
+
+// Reads a G1 point, checking it is on the curve
+fn read_g1<R: Read>(mut reader: R) -> Result<G1Affine> {
+    let x = read_fq(&mut reader)?;
+    let y = read_fq(&mut reader)?;
+    G1Affine::from_xy_checked(x, y).map_err(|e| Error::Format(format!("invalid G1 point: {}", e)))
+}
+
+// Same as `read_g1` for G2, coordinates are `c0` then `c1`
+fn read_g2<R: Read>(mut reader: R) -> Result<G2Affine> {
+    let x = Fq2 {
+        c0: read_fq(&mut reader)?,
+        c1: read_fq(&mut reader)?,
+    };
+    let y = Fq2 {
+        c0: read_fq(&mut reader)?,
+        c1: read_fq(&mut reader)?,
+    };
+    G2Affine::from_xy_checked(x, y).map_err(|e| Error::Format(format!("invalid G2 point: {}", e)))
+}
+
+/// Reads only the header, e.g. to find out the largest power a file provides.
+pub fn header_from_reader<R: Read + Seek>(mut reader: R) -> Result<Header> {
+    let sections = read_sections(&mut reader)?;
+    read_header(&mut reader, &sections)
+}
+
+/// Reads the first 2^power tau powers in G1 and the first two in G2 as a srs in monomial form.
+pub fn from_reader<R: Read + Seek>(mut reader: R, power: u32) -> Result<Crs<Bn256, CrsForMonomialForm>> {
+    let sections = read_sections(&mut reader)?;
+    let header = read_header(&mut reader, &sections)?;
+    check_curve(&header)?;
+    if power > header.power {
+        return Err(Error::SrsTooSmall {
+            required: 1usize.checked_shl(power).unwrap_or(usize::MAX),
+            available: 1 << header.power,
+        });
+    }
+
+    // the header power is at most MAX_POWER, the shifts can't overflow
+    let size = 1u64 << header.power;
+    seek_section(&mut reader, &sections, SECTION_TAU_G1, "tauG1", section_len(size * 2 - 1, G1_SIZE)?)?;
+    let g1_bases = (0..1usize << power).map(|_| read_g1(&mut reader)).collect::<Result<Vec<_>>>()?;
+    if g1_bases[0] != G1Affine::one() {
+        return Err(Error::Format("the first tauG1 point is not the generator".to_owned()));
+    }
+
+    seek_section(&mut reader, &sections, SECTION_TAU_G2, "tauG2", section_len(size, G2_SIZE)?)?;
+    let g2_bases = vec![read_g2(&mut reader)?, read_g2(&mut reader)?];
+    if g2_bases[0] != G2Affine::one() {
+        return Err(Error::Format("the first tauG2 point is not the generator".to_owned()));
+    }
+
+    Ok(Crs::new(g1_bases, g2_bases))
+}
+
+// Writes a ptau file with the powers of tau = 42, the same as `Crs::crs_42`
+#[cfg(test)]
+pub(crate) fn sample_data(power: u32) -> Vec<u8> {
+    use bellman_ce::pairing::bn256::Fr;
+    use bellman_ce::pairing::{ff::Field, CurveProjective};
+    use byteorder::WriteBytesExt;
+
+    fn write_fq(data: &mut Vec<u8>, fq: &Fq) {
+        fq.into_raw_repr().write_le(data).unwrap();
+    }
+
+    let size = 1usize << power;
+    let tau = Fr::from_str("42").unwrap();
+    let mut g1 = Vec::new();
+    let mut g2 = Vec::new();
+    let mut tau_i = Fr::one();
+    for i in 0..size * 2 - 1 {
+        let (x, y) = G1Affine::one().mul(tau_i.into_repr()).into_affine().into_xy_unchecked();
+        write_fq(&mut g1, &x);
+        write_fq(&mut g1, &y);
+        if i < size {
+            let (x, y) = G2Affine::one().mul(tau_i.into_repr()).into_affine().into_xy_unchecked();
+            for fq in &[x.c0, x.c1, y.c0, y.c1] {
+                write_fq(&mut g2, fq);
+            }
+        }
+        tau_i.mul_assign(&tau);
+    }
+    let mut header = Vec::new();
+    header.write_u32::<LittleEndian>(32).unwrap();
+    header.extend_from_slice(&base_field_prime::<Fq>());
+    header.write_u32::<LittleEndian>(power).unwrap();
+    header.write_u32::<LittleEndian>(28).unwrap();
+
+    let mut data = b"ptau".to_vec();
+    data.write_u32::<LittleEndian>(1).unwrap();
+    data.write_u32::<LittleEndian>(3).unwrap();
+    for (sec_type, section) in &[(SECTION_HEADER, header), (SECTION_TAU_G1, g1), (SECTION_TAU_G2, g2)] {
+        data.write_u32::<LittleEndian>(*sec_type).unwrap();
+        data.write_u64::<LittleEndian>(section.len() as u64).unwrap();
+        data.extend_from_slice(section);
+    }
+    data
+}
+
+#[test]
+fn sample() {
+    use bellman_ce::worker::Worker;
+    use std::io::Cursor;
+
+    let header = header_from_reader(Cursor::new(sample_data(3))).unwrap();
+    assert_eq!(header.n8, 32);
+    assert_eq!(header.power, 3);
+
+    let srs = from_reader(Cursor::new(sample_data(3)), 2).unwrap();
+    let expected = Crs::<Bn256, CrsForMonomialForm>::crs_42(4, &Worker::new());
+    assert_eq!(srs.g1_bases, expected.g1_bases);
+    assert_eq!(srs.g2_monomial_bases, expected.g2_monomial_bases);
+
+    let err = from_reader(Cursor::new(sample_data(3)), 4).err().unwrap();
+    assert!(matches!(
+        err,
+        Error::SrsTooSmall {
+            required: 16,
+            available: 8
+        }
+    ));
+}
+
+#[test]
+fn invalid_point() {
+    use std::io::Cursor;
+
+    let mut data = sample_data(1);
+    // the y coordinate of the second tauG1 point, right after the header section and the first point
+    let offset = 12 + 12 + 44 + 12 + G1_SIZE as usize + 32;
+    data[offset] ^= 1;
+    let err = from_reader(Cursor::new(data), 1).err().unwrap();
+    assert!(matches!(err, Error::Format(_)));
+    assert!(err.to_string().contains("invalid G1 point"));
+}
+
+#[test]
+fn unsupported_power() {
+    use byteorder::ByteOrder;
+    use std::io::Cursor;
+
+    let mut data = sample_data(1);
+    // the power in the header section, after the 32 bytes prime
+    LittleEndian::write_u32(&mut data[12 + 12 + 4 + 32..], 64);
+    let err = header_from_reader(Cursor::new(&data)).err().unwrap();
+    assert!(matches!(err, Error::Format(_)));
+    assert!(from_reader(Cursor::new(&data), 1).is_err());
+    assert!(matches!(
+        from_reader(Cursor::new(sample_data(1)), 64).err().unwrap(),
+        Error::SrsTooSmall { available: 2, .. }
+    ));
+}
@@ -0,0 +1,224 @@
+// Python module, built with `maturin develop --cargo-extra-args="--features python"` or by renaming the shared
+// library of `cargo build --release --features python` to plonkit.so. Field elements are Python ints.
+//
+//   import plonkit
+//   stats = plonkit.analyse("circuit.r1cs", sym="circuit.sym")
+//   print(stats["num_gates"], stats["component_stats"][:3])
+use bellman_ce::pairing::{ff::PrimeField, Engine};
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList, PyLong};
+use pyo3::wrap_pyfunction;
+use std::sync::Arc;
+
+use crate::api::{Prover, Verifier};
+use crate::circom_circuit::CircomCircuit;
+use crate::curve::Curve;
+use crate::error::Error;
+use crate::plonk;
+use crate::reader;
+use crate::utils::repr_to_big;
+use crate::with_curve;
+
+fn to_py_err(e: Error) -> PyErr {
+    match e {
+        Error::Io(e) => PyIOError::new_err(e.to_string()),
+        e => PyValueError::new_err(e.to_string()),
+    }
+}
+
+fn circuit_curve(circuit: &str) -> PyResult<Curve> {
+    Ok(reader::load_r1cs_curve(circuit).map_err(to_py_err)?.unwrap_or(Curve::Bn256))
+}
+
+fn fr_to_py<E: Engine>(py: Python, value: &E::Fr) -> PyResult<PyObject> {
+    Ok(py.get_type::<PyLong>().call1((repr_to_big(value.into_repr()),))?.into())
+}
+
+fn json_to_py(py: Python, value: &serde_json::Value) -> PyResult<PyObject> {
+    Ok(match value {
+        serde_json::Value::Null => py.None(),
+        serde_json::Value::Bool(b) => b.to_object(py),
+        serde_json::Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => n.to_object(py),
+            (None, Some(n)) => n.to_object(py),
+            _ => n.as_f64().unwrap_or_default().to_object(py),
+        },
+        serde_json::Value::String(s) => s.to_object(py),
+        serde_json::Value::Array(values) => {
+            let list = PyList::empty(py);
+            for value in values {
+                list.append(json_to_py(py, value)?)?;
+            }
+            list.into()
+        }
+        serde_json::Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, value) in map {
+                dict.set_item(key, json_to_py(py, value)?)?;
+            }
+            dict.into()
+        }
+    })
+}
+
+fn load_circuit<E: Engine>(circuit: &str, sym: Option<&str>) -> PyResult<CircomCircuit<E>> {
+    let (r1cs, wire_mapping) = reader::load_r1cs::<E>(circuit).map_err(to_py_err)?;
+    let symbols = match sym {
+        Some(sym) => Some(Arc::new(reader::load_sym(sym).map_err(to_py_err)?)),
+        None => None,
+    };
+    Ok(CircomCircuit {
+        r1cs,
+        witness: None,
+        wire_mapping,
+        aux_offset: plonk::AUX_OFFSET,
+        symbols,
+    })
+}
+
+fn analyse_for<E: Engine>(py: Python, circuit: &str, top: usize, sym: Option<&str>) -> PyResult<PyObject> {
+    let circuit = load_circuit::<E>(circuit, sym)?;
+    let stats = py.allow_threads(|| plonk::analyse(circuit, top)).map_err(to_py_err)?;
+    let stats = serde_json::to_value(&stats).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    json_to_py(py, &stats)
+}
+
+/// Gate and constraint counts of a circuit, the `analyse.json` of `plonkit analyse` as a dict.
+#[pyfunction(top = "10", sym = "None")]
+#[text_signature = "(circuit, top=10, sym=None)"]
+fn analyse(py: Python, circuit: &str, top: usize, sym: Option<&str>) -> PyResult<PyObject> {
+    with_curve!(circuit_curve(circuit)?, analyse_for(py, circuit, top, sym))
+}
+
+fn load_r1cs_for<E: Engine>(py: Python, circuit: &str) -> PyResult<PyObject> {
+    let (r1cs, wire_mapping) = reader::load_r1cs::<E>(circuit).map_err(to_py_err)?;
+    let lc_to_py = |lc: &[(usize, E::Fr)]| -> PyResult<PyObject> {
+        let terms = lc
+            .iter()
+            .map(|(wire, coeff)| Ok((*wire, fr_to_py::<E>(py, coeff)?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(terms.into_py(py))
+    };
+    let constraints = PyList::empty(py);
+    for (a, b, c) in &r1cs.constraints {
+        constraints.append((lc_to_py(a)?, lc_to_py(b)?, lc_to_py(c)?))?;
+    }
+    let dict = PyDict::new(py);
+    dict.set_item("num_inputs", r1cs.num_inputs)?;
+    dict.set_item("num_aux", r1cs.num_aux)?;
+    dict.set_item("num_variables", r1cs.num_variables)?;
+    dict.set_item("constraints", constraints)?;
+    dict.set_item("wire_mapping", wire_mapping)?;
+    Ok(dict.into())
+}
+
+/// Loads a .r1cs or JSON circuit as a dict. Constraints are `(a, b, c)` tuples of `[(wire, coeff)]` lists.
+#[pyfunction]
+#[text_signature = "(circuit)"]
+fn load_r1cs(py: Python, circuit: &str) -> PyResult<PyObject> {
+    with_curve!(circuit_curve(circuit)?, load_r1cs_for(py, circuit))
+}
+
+fn load_witness_for<E: Engine>(py: Python, witness: &str) -> PyResult<Vec<PyObject>> {
+    let witness = reader::load_witness_from_file::<E>(witness).map_err(to_py_err)?;
+    witness.iter().map(|w| fr_to_py::<E>(py, w)).collect()
+}
+
+/// Loads a .wtns or JSON witness as a list of ints.
+#[pyfunction(curve = "\"bn256\"")]
+#[text_signature = "(witness, curve=\"bn256\")"]
+fn load_witness(py: Python, witness: &str, curve: &str) -> PyResult<Vec<PyObject>> {
+    let curve: Curve = curve.parse().map_err(PyValueError::new_err)?;
+    with_curve!(curve, load_witness_for(py, witness))
+}
+
+fn check_witness_for<E: Engine>(py: Python, circuit: &str, witness: &str, sym: Option<&str>) -> PyResult<PyObject> {
+    let mut circuit = load_circuit::<E>(circuit, sym)?;
+    circuit.set_witness(reader::load_witness_from_file::<E>(witness).map_err(to_py_err)?);
+    let report = circuit.check_witness().map_err(to_py_err)?;
+    let is_valid = report.is_valid();
+    let mut report = serde_json::to_value(&report).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    report["is_valid"] = serde_json::Value::Bool(is_valid);
+    json_to_py(py, &report)
+}
+
+/// Evaluates a witness against every constraint, like `plonkit check-witness --output`.
+#[pyfunction(sym = "None")]
+#[text_signature = "(circuit, witness, sym=None)"]
+fn check_witness(py: Python, circuit: &str, witness: &str, sym: Option<&str>) -> PyResult<PyObject> {
+    with_curve!(circuit_curve(circuit)?, check_witness_for(py, circuit, witness, sym))
+}
+
+fn prove_for<E: Engine>(
+    py: Python,
+    circuit: &str,
+    witness: &str,
+    srs_monomial_form: &str,
+    srs_lagrange_form: Option<String>,
+) -> PyResult<PyObject> {
+    // loading the srs, the setup and proving take long, other python threads run meanwhile
+    let artifacts = py
+        .allow_threads(|| {
+            let (r1cs, wire_mapping) = reader::load_r1cs::<E>(circuit)?;
+            let witness = reader::load_witness_from_file::<E>(witness)?;
+            let mut builder = Prover::builder()
+                .circuit(r1cs, wire_mapping)
+                .srs_monomial(reader::load_key_monomial_form(srs_monomial_form)?);
+            if let Some(key_lagrange_form) = reader::maybe_load_key_lagrange_form(srs_lagrange_form)? {
+                builder = builder.srs_lagrange(key_lagrange_form);
+            }
+            builder.build()?.prove(witness)
+        })
+        .map_err(to_py_err)?;
+    let mut proof = Vec::new();
+    artifacts.proof.write(&mut proof).map_err(|e| to_py_err(e.into()))?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("proof", PyBytes::new(py, &proof))?;
+    let inputs = artifacts
+        .proof
+        .input_values
+        .iter()
+        .map(|x| fr_to_py::<E>(py, x))
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("inputs", inputs)?;
+    dict.set_item("serialized_proof", artifacts.serialized_proof)?;
+    Ok(dict.into())
+}
+
+/// Proves a witness. Returns a dict with the bytes of a proof.bin as `proof`, the public `inputs` and, on bn256,
+/// the `serialized_proof` strings of proof.json.
+#[pyfunction(srs_lagrange_form = "None")]
+#[text_signature = "(circuit, witness, srs_monomial_form, srs_lagrange_form=None)"]
+fn prove(py: Python, circuit: &str, witness: &str, srs_monomial_form: &str, srs_lagrange_form: Option<String>) -> PyResult<PyObject> {
+    with_curve!(
+        circuit_curve(circuit)?,
+        prove_for(py, circuit, witness, srs_monomial_form, srs_lagrange_form)
+    )
+}
+
+fn verify_for<E: Engine>(vk: &str, proof: &[u8]) -> PyResult<bool> {
+    let vk = reader::load_verification_key::<E>(vk).map_err(to_py_err)?;
+    let proof = reader::load_proof_from_reader::<E, _>(proof).map_err(to_py_err)?;
+    Verifier::from_vk(vk).verify(&proof).map_err(to_py_err)
+}
+
+/// Verifies the bytes of a proof.bin, e.g. the `proof` returned by `prove`, against a vk.bin file.
+#[pyfunction(curve = "\"bn256\"")]
+#[text_signature = "(vk, proof, curve=\"bn256\")"]
+fn verify(vk: &str, proof: &[u8], curve: &str) -> PyResult<bool> {
+    let curve: Curve = curve.parse().map_err(PyValueError::new_err)?;
+    with_curve!(curve, verify_for(vk, proof))
+}
+
+#[pymodule]
+fn plonkit(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(analyse, m)?)?;
+    m.add_function(wrap_pyfunction!(load_r1cs, m)?)?;
+    m.add_function(wrap_pyfunction!(load_witness, m)?)?;
+    m.add_function(wrap_pyfunction!(check_witness, m)?)?;
+    m.add_function(wrap_pyfunction!(prove, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    Ok(())
+}
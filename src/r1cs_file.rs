@@ -1,12 +1,17 @@
-#![allow(unused_variables, dead_code)]
 use crate::circom_circuit::Constraint;
+use crate::curve::{field_prime, Curve};
+use crate::error::{Error, Result};
+#[cfg(test)]
+use bellman_ce::pairing::bn256::Bn256;
 use bellman_ce::pairing::{
-    bn256::Bn256,
     ff::{Field, PrimeField, PrimeFieldRepr},
     Engine,
 };
 use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Error, ErrorKind, Read, Result};
+use std::collections::BTreeMap;
+#[cfg(test)]
+use std::io::Cursor;
+use std::io::{Read, Seek, SeekFrom};
 
 pub struct Header {
     pub field_size: u32,
@@ -29,7 +34,7 @@ pub struct R1CSFile<E: Engine> {
 fn read_field<R: Read, E: Engine>(mut reader: R) -> Result<E::Fr> {
     let mut repr = E::Fr::zero().into_repr();
     repr.read_le(&mut reader)?;
-    let fr = E::Fr::from_repr(repr).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    let fr = E::Fr::from_repr(repr).map_err(|e| Error::Format(e.to_string()))?;
     Ok(fr)
 }
 
@@ -38,7 +43,7 @@ fn read_header<R: Read>(mut reader: R, size: u64) -> Result<Header> {
     let mut prime_size = vec![0u8; field_size as usize];
     reader.read_exact(&mut prime_size)?;
     if size != 32 + field_size as u64 {
-        return Err(Error::new(ErrorKind::InvalidData, "Invalid header section size"));
+        return Err(Error::Format("Invalid header section size".to_owned()));
     }
 
     Ok(Header {
@@ -53,7 +58,7 @@ fn read_header<R: Read>(mut reader: R, size: u64) -> Result<Header> {
     })
 }
 
-fn read_constraint_vec<R: Read, E: Engine>(mut reader: R, header: &Header) -> Result<Vec<(usize, E::Fr)>> {
+fn read_constraint_vec<R: Read, E: Engine>(mut reader: R) -> Result<Vec<(usize, E::Fr)>> {
     let n_vec = reader.read_u32::<LittleEndian>()? as usize;
     let mut vec = Vec::with_capacity(n_vec);
     for _ in 0..n_vec {
@@ -62,14 +67,13 @@ fn read_constraint_vec<R: Read, E: Engine>(mut reader: R, header: &Header) -> Re
     Ok(vec)
 }
 
-fn read_constraints<R: Read, E: Engine>(mut reader: R, size: u64, header: &Header) -> Result<Vec<Constraint<E>>> {
-    // todo check section size
+fn read_constraints<R: Read, E: Engine>(mut reader: R, header: &Header) -> Result<Vec<Constraint<E>>> {
     let mut vec = Vec::with_capacity(header.n_constraints as usize);
     for _ in 0..header.n_constraints {
         vec.push((
-            read_constraint_vec::<&mut R, E>(&mut reader, &header)?,
-            read_constraint_vec::<&mut R, E>(&mut reader, &header)?,
-            read_constraint_vec::<&mut R, E>(&mut reader, &header)?,
+            read_constraint_vec::<&mut R, E>(&mut reader)?,
+            read_constraint_vec::<&mut R, E>(&mut reader)?,
+            read_constraint_vec::<&mut R, E>(&mut reader)?,
         ));
     }
     Ok(vec)
@@ -77,51 +81,120 @@ fn read_constraints<R: Read, E: Engine>(mut reader: R, size: u64, header: &Heade
 
 fn read_map<R: Read>(mut reader: R, size: u64, header: &Header) -> Result<Vec<u64>> {
     if size != header.n_wires as u64 * 8 {
-        return Err(Error::new(ErrorKind::InvalidData, "Invalid map section size"));
+        return Err(Error::Format("Invalid map section size".to_owned()));
     }
     let mut vec = Vec::with_capacity(header.n_wires as usize);
     for _ in 0..header.n_wires {
         vec.push(reader.read_u64::<LittleEndian>()?);
     }
     if vec[0] != 0 {
-        return Err(Error::new(ErrorKind::InvalidData, "Wire 0 should always be mapped to 0"));
+        return Err(Error::Format("Wire 0 should always be mapped to 0".to_owned()));
     }
     Ok(vec)
 }
 
-pub fn from_reader<R: Read>(mut reader: R) -> Result<R1CSFile<Bn256>> {
+const SECTION_HEADER: u32 = 1;
+const SECTION_CONSTRAINTS: u32 = 2;
+const SECTION_WIRE_MAP: u32 = 3;
+// circom 2 custom gates, emitted for circuits using the `custom_templates` pragma
+const SECTION_CUSTOM_GATES_LIST: u32 = 4;
+const SECTION_CUSTOM_GATES_APPLICATION: u32 = 5;
+
+struct Section {
+    offset: u64,
+    size: u64,
+}
+
+// Seeks to the beginning of a section and returns its size
+fn seek_section<R: Seek>(mut reader: R, sections: &BTreeMap<u32, Section>, sec_type: u32) -> Result<Option<u64>> {
+    match sections.get(&sec_type) {
+        Some(section) => {
+            reader.seek(SeekFrom::Start(section.offset))?;
+            Ok(Some(section.size))
+        }
+        None => Ok(None),
+    }
+}
+
+fn check_section_end<R: Seek>(mut reader: R, sections: &BTreeMap<u32, Section>, sec_type: u32, name: &str) -> Result<()> {
+    let section = &sections[&sec_type];
+    let consumed = reader.seek(SeekFrom::Current(0))? - section.offset;
+    if consumed != section.size {
+        return Err(Error::Format(format!(
+            "Invalid {} section size: declared {} bytes, read {} bytes",
+            name, section.size, consumed
+        )));
+    }
+    Ok(())
+}
+
+// Locates all sections, they may come in any order
+fn read_sections<R: Read + Seek>(mut reader: R) -> Result<(u32, BTreeMap<u32, Section>)> {
     let mut magic = [0u8; 4];
     reader.read_exact(&mut magic)?;
     if magic != [0x72, 0x31, 0x63, 0x73] {
         // magic = "r1cs"
-        return Err(Error::new(ErrorKind::InvalidData, "Invalid magic number"));
+        return Err(Error::Format("Invalid magic number".to_owned()));
     }
 
     let version = reader.read_u32::<LittleEndian>()?;
     if version != 1 {
-        return Err(Error::new(ErrorKind::InvalidData, "Unsupported version"));
+        return Err(Error::Format("Unsupported version".to_owned()));
     }
 
     let num_sections = reader.read_u32::<LittleEndian>()?;
+    let mut sections = BTreeMap::new();
+    for _ in 0..num_sections {
+        let sec_type = reader.read_u32::<LittleEndian>()?;
+        let sec_size = reader.read_u64::<LittleEndian>()?;
+        let offset = reader.seek(SeekFrom::Current(0))?;
+        match sec_type {
+            SECTION_HEADER | SECTION_CONSTRAINTS | SECTION_WIRE_MAP => {}
+            SECTION_CUSTOM_GATES_LIST | SECTION_CUSTOM_GATES_APPLICATION => {
+                return Err(Error::Format("Circuits with circom 2 custom gates are not supported".to_owned()));
+            }
+            _ => log::warn!("Skipping unknown r1cs section type {} ({} bytes)", sec_type, sec_size),
+        }
+        if sections.insert(sec_type, Section { offset, size: sec_size }).is_some() {
+            return Err(Error::Format(format!("Duplicated section type {}", sec_type)));
+        }
+        reader.seek(SeekFrom::Start(offset + sec_size))?;
+    }
+    Ok((version, sections))
+}
 
-    // todo: rewrite this to support different section order and unknown sections
-    // todo: handle sec_size correctly
-    let sec_type = reader.read_u32::<LittleEndian>()?;
-    let sec_size = reader.read_u64::<LittleEndian>()?;
+fn read_header_section<R: Read + Seek>(mut reader: R, sections: &BTreeMap<u32, Section>) -> Result<Header> {
+    let sec_size =
+        seek_section(&mut reader, sections, SECTION_HEADER)?.ok_or_else(|| Error::Format("Missing header section".to_owned()))?;
     let header = read_header(&mut reader, sec_size)?;
-    if header.field_size != 32 {
-        return Err(Error::new(ErrorKind::InvalidData, "This parser only supports 32-byte fields"));
-    }
-    if header.prime_size != hex!("010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430") {
-        return Err(Error::new(ErrorKind::InvalidData, "This parser only supports bn256"));
+    check_section_end(&mut reader, sections, SECTION_HEADER, "header")?;
+    Ok(header)
+}
+
+/// Reads only the header, e.g. to find out the curve of a circuit from its prime.
+pub fn header_from_reader<R: Read + Seek>(mut reader: R) -> Result<Header> {
+    let (_, sections) = read_sections(&mut reader)?;
+    read_header_section(&mut reader, &sections)
+}
+
+pub fn from_reader<R: Read + Seek, E: Engine>(mut reader: R) -> Result<R1CSFile<E>> {
+    let (version, sections) = read_sections(&mut reader)?;
+
+    let header = read_header_section(&mut reader, &sections)?;
+    if header.prime_size != field_prime::<E>() {
+        let curve = Curve::from_prime(&header.prime_size).map_or_else(|| "an unsupported curve".to_owned(), |c| c.to_string());
+        return Err(Error::FieldMismatch(format!("the circuit is compiled for {}", curve)));
     }
-    let sec_type = reader.read_u32::<LittleEndian>()?;
-    let sec_size = reader.read_u64::<LittleEndian>()?;
-    let constraints = read_constraints::<&mut R, Bn256>(&mut reader, sec_size, &header)?;
 
-    let sec_type = reader.read_u32::<LittleEndian>()?;
-    let sec_size = reader.read_u64::<LittleEndian>()?;
-    let wire_mapping = read_map(&mut reader, sec_size, &header)?;
+    seek_section(&mut reader, &sections, SECTION_CONSTRAINTS)?.ok_or_else(|| Error::Format("Missing constraints section".to_owned()))?;
+    let constraints = read_constraints::<&mut R, E>(&mut reader, &header)?;
+    check_section_end(&mut reader, &sections, SECTION_CONSTRAINTS, "constraints")?;
+
+    // the map section is optional, without it every wire is its own label
+    let wire_mapping = match seek_section(&mut reader, &sections, SECTION_WIRE_MAP)? {
+        Some(sec_size) => read_map(&mut reader, sec_size, &header)?,
+        None => (0..header.n_wires as u64).collect(),
+    };
 
     Ok(R1CSFile {
         version,
@@ -131,9 +204,9 @@ pub fn from_reader<R: Read>(mut reader: R) -> Result<R1CSFile<Bn256>> {
     })
 }
 
-#[test]
-fn sample() {
-    let data = hex!(
+#[cfg(test)]
+pub(crate) fn sample_data() -> Vec<u8> {
+    hex!(
         "
         72316373
         01000000
@@ -183,10 +256,44 @@ fn sample() {
         0f000000 00000000
         44010000 00000000
     "
-    );
+    )
+    .to_vec()
+}
+
+// Splits a file into its sections, assuming they are laid out one after another
+#[cfg(test)]
+fn split_sections(data: &[u8]) -> Vec<(u32, Vec<u8>)> {
+    let mut reader = Cursor::new(data);
+    reader.set_position(8);
+    let num_sections = reader.read_u32::<LittleEndian>().unwrap();
+    (0..num_sections)
+        .map(|_| {
+            let sec_type = reader.read_u32::<LittleEndian>().unwrap();
+            let mut section = vec![0u8; reader.read_u64::<LittleEndian>().unwrap() as usize];
+            reader.read_exact(&mut section).unwrap();
+            (sec_type, section)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn join_sections(sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    use byteorder::WriteBytesExt;
+    let mut data = b"r1cs".to_vec();
+    data.write_u32::<LittleEndian>(1).unwrap();
+    data.write_u32::<LittleEndian>(sections.len() as u32).unwrap();
+    for (sec_type, section) in sections {
+        data.write_u32::<LittleEndian>(*sec_type).unwrap();
+        data.write_u64::<LittleEndian>(section.len() as u64).unwrap();
+        data.extend_from_slice(section);
+    }
+    data
+}
 
+#[test]
+fn sample() {
     use bellman_ce::pairing::ff;
-    let file = from_reader(&data[..]).unwrap();
+    let file = from_reader::<_, Bn256>(Cursor::new(sample_data())).unwrap();
     assert_eq!(file.version, 1);
 
     assert_eq!(file.header.field_size, 32);
@@ -212,3 +319,58 @@ fn sample() {
     assert_eq!(file.wire_mapping.len(), 7);
     assert_eq!(file.wire_mapping[1], 3);
 }
+
+#[test]
+fn shuffled_sections() {
+    let mut sections = split_sections(&sample_data());
+    sections.reverse();
+    let file = from_reader::<_, Bn256>(Cursor::new(join_sections(&sections))).unwrap();
+    assert_eq!(file.header.n_wires, 7);
+    assert_eq!(file.constraints.len(), 3);
+    assert_eq!(file.constraints[0].0[0].0, 5);
+    assert_eq!(file.wire_mapping[1], 3);
+}
+
+#[test]
+fn unknown_section() {
+    let mut sections = split_sections(&sample_data());
+    sections.insert(1, (0x10, vec![1, 2, 3]));
+    let file = from_reader::<_, Bn256>(Cursor::new(join_sections(&sections))).unwrap();
+    assert_eq!(file.constraints.len(), 3);
+    assert_eq!(file.wire_mapping[1], 3);
+}
+
+#[test]
+fn missing_map_section() {
+    let mut sections = split_sections(&sample_data());
+    sections.retain(|(sec_type, _)| *sec_type != SECTION_WIRE_MAP);
+    let file = from_reader::<_, Bn256>(Cursor::new(join_sections(&sections))).unwrap();
+    assert_eq!(file.wire_mapping, (0..7).collect::<Vec<u64>>());
+}
+
+#[test]
+fn section_size_mismatch() {
+    let mut sections = split_sections(&sample_data());
+    sections[1].1.extend_from_slice(&[0, 0, 0, 0]);
+    let err = from_reader::<_, Bn256>(Cursor::new(join_sections(&sections))).err().unwrap();
+    assert!(matches!(err, Error::Format(_)));
+    assert!(err.to_string().contains("constraints section size"));
+}
+
+#[test]
+fn custom_gates() {
+    let mut sections = split_sections(&sample_data());
+    sections.push((SECTION_CUSTOM_GATES_LIST, vec![0, 0, 0, 0]));
+    let err = from_reader::<_, Bn256>(Cursor::new(join_sections(&sections))).err().unwrap();
+    assert!(err.to_string().contains("custom gates"));
+}
+
+#[test]
+fn field_mismatch() {
+    use bellman_ce::pairing::bls12_381::Bls12;
+    let err = from_reader::<_, Bls12>(Cursor::new(sample_data())).err().unwrap();
+    assert!(matches!(err, Error::FieldMismatch(_)));
+    assert!(err.to_string().contains("compiled for bn256"));
+    let header = header_from_reader(Cursor::new(sample_data())).unwrap();
+    assert_eq!(Curve::from_prime(&header.prime_size), Some(Curve::Bn256));
+}
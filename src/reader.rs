@@ -1,14 +1,20 @@
-use anyhow::{bail, format_err};
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use itertools::Itertools;
+use num_bigint::BigUint;
+use num_traits::Num;
 use std::collections::BTreeMap;
-use std::fs::{File, OpenOptions};
-use std::io::{BufReader, Read};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::path::Path;
 use std::str;
 
 use bellman_ce::{
     kate_commitment::{Crs, CrsForLagrangeForm, CrsForMonomialForm},
-    pairing::{bn256::Bn256, ff::PrimeField, Engine},
+    pairing::{
+        bn256::{Bn256, Fq, Fr, G1Affine},
+        ff::PrimeField,
+        CurveAffine, Engine,
+    },
     plonk::{
         better_cs::cs::PlonkCsWidth4WithNextStepParams,
         better_cs::keys::{Proof, VerificationKey},
@@ -17,55 +23,183 @@ use bellman_ce::{
 };
 
 use crate::circom_circuit::{CircuitJson, R1CS};
+use crate::curve::{field_prime, Curve};
+use crate::encoding;
+use crate::error::{Error, Result};
+use crate::ignition;
+use crate::ptau_file;
+use crate::sym_file::SymbolTable;
+
+// io errors of `File::open` do not tell which file could not be opened
+fn open_file(filename: &str) -> Result<File> {
+    File::open(filename).map_err(|e| Error::Io(std::io::Error::new(e.kind(), format!("{}: {}", filename, e))))
+}
+
+fn read_file(filename: &str) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    open_file(filename)?.read_to_end(&mut data)?;
+    Ok(data)
+}
 
 ///
 /// proof
 ///
 
-pub fn load_proof<E: Engine>(filename: &str) -> Proof<E, PlonkCsWidth4WithNextStepParams> {
-    Proof::<E, PlonkCsWidth4WithNextStepParams>::read(File::open(filename).expect("read proof file err")).expect("read proof err")
+/// Loads a proof in the binary encoding of bellman or in the JSON one of `encoding`.
+pub fn load_proof<E: Engine>(filename: &str) -> Result<Proof<E, PlonkCsWidth4WithNextStepParams>> {
+    let data = read_file(filename)?;
+    if encoding::is_json(&data) {
+        encoding::read_proof_json(data.as_slice())
+    } else {
+        load_proof_from_reader(data.as_slice())
+    }
+}
+
+pub fn load_proof_from_reader<E: Engine, R: Read>(reader: R) -> Result<Proof<E, PlonkCsWidth4WithNextStepParams>> {
+    Ok(Proof::<E, PlonkCsWidth4WithNextStepParams>::read(reader)?)
+}
+
+/// Number of values in a proof serialized for the solidity verifier, as in proof.json.
+pub const SERIALIZED_PROOF_LEN: usize = 33;
+
+/// Rebuilds a bn256 proof from its solidity encoding, the inverse of `bellman_vk_codegen::serialize_proof`.
+/// Values are decimal or 0x prefixed hex strings, `n` is the domain size of the verification key.
+pub fn load_proof_from_serialized(
+    inputs: &[String],
+    serialized_proof: &[String],
+    n: usize,
+) -> Result<Proof<Bn256, PlonkCsWidth4WithNextStepParams>> {
+    if serialized_proof.len() != SERIALIZED_PROOF_LEN {
+        return Err(Error::Format(format!(
+            "serialized proof has {} values instead of {}",
+            serialized_proof.len(),
+            SERIALIZED_PROOF_LEN
+        )));
+    }
+    // 9 points of commitments, 11 evaluations and the 2 opening proofs
+    let g1 = |values: &[String]| values.chunks(2).map(|xy| parse_g1(&xy[0], &xy[1])).collect::<Result<Vec<_>>>();
+    let commitments = g1(&serialized_proof[0..18])?;
+    let evaluations = serialized_proof[18..29]
+        .iter()
+        .map(|x| parse_field::<Fr>(x))
+        .collect::<Result<Vec<_>>>()?;
+    let openings = g1(&serialized_proof[29..33])?;
+
+    let mut proof = Proof::<Bn256, PlonkCsWidth4WithNextStepParams>::empty();
+    proof.n = n;
+    proof.num_inputs = inputs.len();
+    proof.input_values = inputs.iter().map(|x| parse_field::<Fr>(x)).collect::<Result<Vec<_>>>()?;
+    proof.wire_commitments = commitments[0..4].to_vec();
+    proof.grand_product_commitment = commitments[4];
+    proof.quotient_poly_commitments = commitments[5..9].to_vec();
+    proof.wire_values_at_z = evaluations[0..4].to_vec();
+    proof.wire_values_at_z_omega = vec![evaluations[4]];
+    proof.grand_product_at_z_omega = evaluations[5];
+    proof.quotient_polynomial_at_z = evaluations[6];
+    proof.linearization_polynomial_at_z = evaluations[7];
+    proof.permutation_polynomials_at_z = evaluations[8..11].to_vec();
+    proof.opening_at_z_proof = openings[0];
+    proof.opening_at_z_omega_proof = openings[1];
+    Ok(proof)
+}
+
+/// Loads the proof.json and public.json written by `prove`, `n` is the domain size of the verification key.
+pub fn load_proof_from_json_files(proofjson: &str, publicjson: &str, n: usize) -> Result<Proof<Bn256, PlonkCsWidth4WithNextStepParams>> {
+    let serialized_proof: Vec<String> = serde_json::from_reader(BufReader::new(open_file(proofjson)?))?;
+    let inputs: Vec<String> = serde_json::from_reader(BufReader::new(open_file(publicjson)?))?;
+    load_proof_from_serialized(&inputs, &serialized_proof, n)
+}
+
+// U256 values of bellman_vk_codegen are serialized as minimal hex, without leading zeros
+fn parse_field<F: PrimeField>(value: &str) -> Result<F> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(digits) => BigUint::from_str_radix(digits, 16)
+            .ok()
+            .and_then(|n| F::from_str(&n.to_str_radix(10))),
+        None => F::from_str(value),
+    };
+    parsed.ok_or_else(|| Error::Format(format!("invalid field element {}", value)))
+}
+
+fn parse_g1(x: &str, y: &str) -> Result<G1Affine> {
+    let (x, y) = (parse_field::<Fq>(x)?, parse_field::<Fq>(y)?);
+    // the point at infinity has no affine coordinates, it is encoded with x = 0
+    if x.is_zero() && (y.is_zero() || y == Fq::one()) {
+        return Ok(G1Affine::zero());
+    }
+    G1Affine::from_xy_checked(x, y).map_err(|e| Error::Format(format!("invalid curve point: {}", e)))
 }
 
 ///
 /// verification key
 ///
 
-pub fn load_verification_key<E: Engine>(filename: &str) -> VerificationKey<E, PlonkCsWidth4WithNextStepParams> {
-    let mut reader = std::io::BufReader::with_capacity(1 << 24, File::open(filename).expect("read vk file err"));
-    VerificationKey::<E, PlonkCsWidth4WithNextStepParams>::read(&mut reader).expect("read vk err")
+/// Loads a verification key in the binary encoding of bellman or in the JSON one of `encoding`.
+pub fn load_verification_key<E: Engine>(filename: &str) -> Result<VerificationKey<E, PlonkCsWidth4WithNextStepParams>> {
+    let data = read_file(filename)?;
+    if encoding::is_json(&data) {
+        encoding::read_verification_key_json(data.as_slice())
+    } else {
+        load_verification_key_from_reader(data.as_slice())
+    }
+}
+
+pub fn load_verification_key_from_reader<E: Engine, R: Read>(mut reader: R) -> Result<VerificationKey<E, PlonkCsWidth4WithNextStepParams>> {
+    Ok(VerificationKey::<E, PlonkCsWidth4WithNextStepParams>::read(&mut reader)?)
 }
 
 ///
 /// universal setup
 ///
 
-fn get_universal_setup_file_buff_reader(setup_file_name: &str) -> Result<BufReader<File>, anyhow::Error> {
-    let setup_file =
-        File::open(setup_file_name).map_err(|e| format_err!("Failed to open universal setup file {}, err: {}", setup_file_name, e))?;
-    Ok(BufReader::with_capacity(1 << 29, setup_file))
+fn get_universal_setup_file_buff_reader(setup_file_name: &str) -> Result<BufReader<File>> {
+    Ok(BufReader::with_capacity(1 << 29, open_file(setup_file_name)?))
+}
+
+pub fn load_key_monomial_form<E: Engine>(filename: &str) -> Result<Crs<E, CrsForMonomialForm>> {
+    load_key_monomial_form_from_reader(get_universal_setup_file_buff_reader(filename)?)
+}
+
+pub fn load_key_monomial_form_from_reader<E: Engine, R: Read>(mut reader: R) -> Result<Crs<E, CrsForMonomialForm>> {
+    Ok(Crs::<E, CrsForMonomialForm>::read(&mut reader)?)
 }
 
-pub fn load_key_monomial_form<E: Engine>(filename: &str) -> Crs<E, CrsForMonomialForm> {
-    let mut buf_reader = get_universal_setup_file_buff_reader(filename).expect("read key_monomial_form file err");
-    Crs::<E, CrsForMonomialForm>::read(&mut buf_reader).expect("read key_monomial_form err")
+/// Imports the first 2^power tau powers of a snarkjs .ptau file as a srs in monomial form.
+pub fn load_key_monomial_form_from_ptau(filename: &str, power: u32) -> Result<Crs<Bn256, CrsForMonomialForm>> {
+    ptau_file::from_reader(get_universal_setup_file_buff_reader(filename)?, power)
 }
 
-pub fn maybe_load_key_lagrange_form<E: Engine>(option_filename: Option<String>) -> Option<Crs<E, CrsForLagrangeForm>> {
+/// Assembles a srs of 2^power points in monomial form from the transcriptNN.dat files of Aztec ignition in `dir`.
+pub fn load_key_monomial_form_from_ignition(dir: &str, power: u32) -> Result<Crs<Bn256, CrsForMonomialForm>> {
+    ignition::from_transcripts(
+        |number| {
+            let transcript = Path::new(dir).join(format!("transcript{:02}.dat", number));
+            log::info!("Loading ignition transcript {}...", transcript.display());
+            read_file(&transcript.to_string_lossy())
+        },
+        power,
+    )
+}
+
+pub fn maybe_load_key_lagrange_form<E: Engine>(option_filename: Option<String>) -> Result<Option<Crs<E, CrsForLagrangeForm>>> {
     match option_filename {
-        None => None,
+        None => Ok(None),
         Some(filename) => {
-            let mut buf_reader = get_universal_setup_file_buff_reader(&filename).expect("read key_lagrange_form file err");
-            let key_lagrange_form = Crs::<E, CrsForLagrangeForm>::read(&mut buf_reader).expect("read key_lagrange_form err");
-            Some(key_lagrange_form)
+            let key_lagrange_form = load_key_lagrange_form_from_reader(get_universal_setup_file_buff_reader(&filename)?)?;
+            Ok(Some(key_lagrange_form))
         }
     }
 }
 
+pub fn load_key_lagrange_form_from_reader<E: Engine, R: Read>(mut reader: R) -> Result<Crs<E, CrsForLagrangeForm>> {
+    Ok(Crs::<E, CrsForLagrangeForm>::read(&mut reader)?)
+}
+
 ///
 /// witness
 ///
 
-pub fn load_witness_from_file<E: Engine>(filename: &str) -> Vec<E::Fr> {
+pub fn load_witness_from_file<E: Engine>(filename: &str) -> Result<Vec<E::Fr>> {
     if filename.ends_with("json") {
         load_witness_from_json_file::<E>(filename)
     } else {
@@ -73,139 +207,243 @@ pub fn load_witness_from_file<E: Engine>(filename: &str) -> Vec<E::Fr> {
     }
 }
 
-pub fn load_witness_from_json_file<E: Engine>(filename: &str) -> Vec<E::Fr> {
-    let reader = OpenOptions::new().read(true).open(filename).expect("unable to open.");
-    load_witness_from_json::<E, BufReader<File>>(BufReader::new(reader))
+pub fn load_witness_from_json_file<E: Engine>(filename: &str) -> Result<Vec<E::Fr>> {
+    load_witness_from_json_reader::<E, BufReader<File>>(BufReader::new(open_file(filename)?))
 }
 
-fn load_witness_from_json<E: Engine, R: Read>(reader: R) -> Vec<E::Fr> {
-    let witness: Vec<String> = serde_json::from_reader(reader).expect("unable to read.");
-    witness.into_iter().map(|x| E::Fr::from_str(&x).unwrap()).collect::<Vec<E::Fr>>()
+/// Reads a witness saved as a JSON array of decimal strings.
+pub fn load_witness_from_json_reader<E: Engine, R: Read>(reader: R) -> Result<Vec<E::Fr>> {
+    let witness: Vec<String> = serde_json::from_reader(reader)?;
+    witness
+        .into_iter()
+        .map(|x| E::Fr::from_str(&x).ok_or_else(|| Error::Format(format!("invalid witness value {}", x))))
+        .collect()
 }
 
-pub fn load_witness_from_bin_file<E: Engine>(filename: &str) -> Vec<E::Fr> {
-    let reader = OpenOptions::new().read(true).open(filename).expect("unable to open.");
-    load_witness_from_bin_reader::<E, BufReader<File>>(BufReader::new(reader)).expect("read witness failed")
+pub fn load_witness_from_bin_file<E: Engine>(filename: &str) -> Result<Vec<E::Fr>> {
+    load_witness_from_bin_reader::<E, BufReader<File>>(BufReader::new(open_file(filename)?))
 }
 
-pub fn load_witness_from_array<E: Engine>(buffer: Vec<u8>) -> Result<Vec<E::Fr>, anyhow::Error> {
+pub fn load_witness_from_array<E: Engine>(buffer: Vec<u8>) -> Result<Vec<E::Fr>> {
     load_witness_from_bin_reader::<E, _>(buffer.as_slice())
 }
 
+///
+/// debug symbols
+///
+
+pub fn load_sym(filename: &str) -> Result<SymbolTable> {
+    crate::sym_file::from_reader(BufReader::new(open_file(filename)?))
+}
+
 ///
 /// r1cs
 ///
 
-pub fn load_r1cs(filename: &str) -> R1CS<Bn256> {
+/// Loads the circuit with its wire map, which is `None` when every wire is its own signal label.
+pub fn load_r1cs<E: Engine>(filename: &str) -> Result<(R1CS<E>, Option<Vec<usize>>)> {
+    let reader = BufReader::new(open_file(filename)?);
     if filename.ends_with("json") {
-        load_r1cs_from_json_file(filename)
+        Ok((load_r1cs_from_json_reader(reader)?, None))
     } else {
-        let (r1cs, _wire_mapping) = load_r1cs_from_bin_file(filename);
-        r1cs
+        load_r1cs_from_bin_reader(reader)
     }
 }
 
-fn load_r1cs_from_json_file<E: Engine>(filename: &str) -> R1CS<E> {
-    let reader = OpenOptions::new().read(true).open(filename).expect("unable to open.");
-    load_r1cs_from_json(BufReader::new(reader))
+/// Same as `load_r1cs` for a circuit in memory, telling .r1cs from JSON by the magic number.
+pub fn load_r1cs_from_array<E: Engine>(buffer: &[u8]) -> Result<(R1CS<E>, Option<Vec<usize>>)> {
+    if buffer.starts_with(b"r1cs") {
+        load_r1cs_from_bin_reader(Cursor::new(buffer))
+    } else {
+        Ok((load_r1cs_from_json_reader(buffer)?, None))
+    }
 }
 
-fn load_r1cs_from_json<E: Engine, R: Read>(reader: R) -> R1CS<E> {
-    let circuit_json: CircuitJson = serde_json::from_reader(reader).expect("unable to read.");
+pub fn load_r1cs_from_json_reader<E: Engine, R: Read>(reader: R) -> Result<R1CS<E>> {
+    let circuit_json: CircuitJson = serde_json::from_reader(reader)?;
 
     let num_inputs = circuit_json.num_inputs + circuit_json.num_outputs + 1;
+    if circuit_json.num_variables < num_inputs {
+        return Err(Error::Format("fewer variables than inputs".to_owned()));
+    }
     let num_aux = circuit_json.num_variables - num_inputs;
 
     let convert_constraint = |lc: &BTreeMap<String, String>| {
         lc.iter()
-            .map(|(index, coeff)| (index.parse().unwrap(), E::Fr::from_str(coeff).unwrap()))
-            .collect_vec()
+            .map(|(index, coeff)| {
+                let index = index.parse().map_err(|_| Error::Format(format!("invalid wire index {}", index)))?;
+                let coeff = E::Fr::from_str(coeff).ok_or_else(|| Error::Format(format!("invalid coefficient {}", coeff)))?;
+                Ok((index, coeff))
+            })
+            .collect::<Result<Vec<_>>>()
     };
 
     let constraints = circuit_json
         .constraints
         .iter()
-        .map(|c| (convert_constraint(&c[0]), convert_constraint(&c[1]), convert_constraint(&c[2])))
-        .collect_vec();
+        .map(|c| Ok((convert_constraint(&c[0])?, convert_constraint(&c[1])?, convert_constraint(&c[2])?)))
+        .collect::<Result<Vec<_>>>()?;
 
-    R1CS {
+    Ok(R1CS {
         num_inputs,
         num_aux,
         num_variables: circuit_json.num_variables,
         constraints,
-    }
+    })
 }
 
-fn load_r1cs_from_bin_file(filename: &str) -> (R1CS<Bn256>, Vec<usize>) {
-    let reader = OpenOptions::new().read(true).open(filename).expect("unable to open.");
-    load_r1cs_from_bin(BufReader::new(reader))
+/// Curve of a circuit, from the prime in its .r1cs header. JSON circuits do not record it.
+pub fn load_r1cs_curve(filename: &str) -> Result<Option<Curve>> {
+    if filename.ends_with("json") {
+        return Ok(None);
+    }
+    let header = crate::r1cs_file::header_from_reader(BufReader::new(open_file(filename)?))?;
+    match Curve::from_prime(&header.prime_size) {
+        Some(curve) => Ok(Some(curve)),
+        None => Err(Error::FieldMismatch("the circuit is compiled for an unsupported curve".to_owned())),
+    }
 }
 
-fn load_r1cs_from_bin<R: Read>(reader: R) -> (R1CS<Bn256>, Vec<usize>) {
-    let file = crate::r1cs_file::from_reader(reader).expect("unable to read.");
+pub fn load_r1cs_from_bin_reader<E: Engine, R: Read + Seek>(reader: R) -> Result<(R1CS<E>, Option<Vec<usize>>)> {
+    let file = crate::r1cs_file::from_reader::<R, E>(reader)?;
     let num_inputs = (1 + file.header.n_pub_in + file.header.n_pub_out) as usize;
     let num_variables = file.header.n_wires as usize;
     let num_aux = num_variables - num_inputs;
-    (
+    let wire_mapping = file.wire_mapping.iter().map(|e| *e as usize).collect_vec();
+    let is_identity = wire_mapping.iter().enumerate().all(|(wire, label)| wire == *label);
+    Ok((
         R1CS {
             num_aux,
             num_inputs,
             num_variables,
             constraints: file.constraints,
         },
-        file.wire_mapping.iter().map(|e| *e as usize).collect_vec(),
-    )
+        if is_identity { None } else { Some(wire_mapping) },
+    ))
 }
 
-fn load_witness_from_bin_reader<E: Engine, R: Read>(mut reader: R) -> Result<Vec<E::Fr>, anyhow::Error> {
+pub fn load_witness_from_bin_reader<E: Engine, R: Read>(mut reader: R) -> Result<Vec<E::Fr>> {
     let mut wtns_header = [0u8; 4];
     reader.read_exact(&mut wtns_header)?;
     if wtns_header != [119, 116, 110, 115] {
         // ruby -e 'p "wtns".bytes' => [119, 116, 110, 115]
-        bail!("invalid file header");
+        return Err(Error::Format("invalid file header".to_owned()));
     }
     let version = reader.read_u32::<LittleEndian>()?;
     log::info!("wtns version {}", version);
     if version > 2 {
-        bail!("unsupported file version");
+        return Err(Error::Format("unsupported file version".to_owned()));
     }
     let num_sections = reader.read_u32::<LittleEndian>()?;
     if num_sections != 2 {
-        bail!("invalid num sections");
+        return Err(Error::Format("invalid num sections".to_owned()));
     }
     // read the first section
     let sec_type = reader.read_u32::<LittleEndian>()?;
     if sec_type != 1 {
-        bail!("invalid section type");
+        return Err(Error::Format("invalid section type".to_owned()));
     }
     let sec_size = reader.read_u64::<LittleEndian>()?;
-    if sec_size != 4 + 32 + 4 {
-        bail!("invalid section len")
+    if sec_size != 4 + field_prime::<E>().len() as u64 + 4 {
+        return Err(Error::Format("invalid section len".to_owned()));
     }
     let field_size = reader.read_u32::<LittleEndian>()?;
-    if field_size != 32 {
-        bail!("invalid field byte size");
+    if field_size as usize != field_prime::<E>().len() {
+        return Err(Error::Format("invalid field byte size".to_owned()));
     }
     let mut prime = vec![0u8; field_size as usize];
     reader.read_exact(&mut prime)?;
-    if prime != hex!("010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430") {
-        bail!("invalid curve prime");
+    if prime != field_prime::<E>() {
+        return Err(Error::FieldMismatch("the witness is over another curve".to_owned()));
     }
     let witness_len = reader.read_u32::<LittleEndian>()?;
     log::info!("witness len {}", witness_len);
     let sec_type = reader.read_u32::<LittleEndian>()?;
     if sec_type != 2 {
-        bail!("invalid section type");
+        return Err(Error::Format("invalid section type".to_owned()));
     }
     let sec_size = reader.read_u64::<LittleEndian>()?;
     if sec_size != (witness_len * field_size) as u64 {
-        bail!("invalid witness section size {}", sec_size);
+        return Err(Error::Format(format!("invalid witness section size {}", sec_size)));
     }
     let mut result = Vec::with_capacity(witness_len as usize);
     for _ in 0..witness_len {
         let mut repr = E::Fr::zero().into_repr();
         repr.read_le(&mut reader)?;
-        result.push(E::Fr::from_repr(repr)?);
+        result.push(E::Fr::from_repr(repr).map_err(|e| Error::Format(e.to_string()))?);
     }
     Ok(result)
 }
+
+/// Writes a witness in the .wtns format read by `load_witness_from_bin_file`.
+pub fn write_witness_to_bin_writer<E: Engine, W: Write>(witness: &[E::Fr], mut writer: W) -> Result<()> {
+    let prime = field_prime::<E>();
+    let field_size = prime.len() as u32;
+    writer.write_all(b"wtns")?;
+    writer.write_u32::<LittleEndian>(2)?; // version
+    writer.write_u32::<LittleEndian>(2)?; // num sections
+    writer.write_u32::<LittleEndian>(1)?;
+    writer.write_u64::<LittleEndian>(4 + field_size as u64 + 4)?;
+    writer.write_u32::<LittleEndian>(field_size)?;
+    writer.write_all(&prime)?;
+    writer.write_u32::<LittleEndian>(witness.len() as u32)?;
+    writer.write_u32::<LittleEndian>(2)?;
+    writer.write_u64::<LittleEndian>(field_size as u64 * witness.len() as u64)?;
+    for w in witness {
+        w.into_repr().write_le(&mut writer)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn wtns_round_trip() {
+    let witness = vec![Fr::one(), Fr::from_str("42").unwrap()];
+    let mut buffer = Vec::new();
+    write_witness_to_bin_writer::<Bn256, _>(&witness, &mut buffer).unwrap();
+    assert_eq!(load_witness_from_array::<Bn256>(buffer).unwrap(), witness);
+}
+
+#[test]
+fn r1cs_from_array() {
+    let (r1cs, wire_mapping) = load_r1cs_from_array::<Bn256>(&crate::r1cs_file::sample_data()).unwrap();
+    assert_eq!(r1cs.num_variables, 7);
+    assert_eq!(r1cs.constraints.len(), 3);
+    assert_eq!(wire_mapping.unwrap()[1], 3);
+
+    let json = br#"{"constraints": [[{"1": "2"}, {"2": "3"}, {"3": "1"}]], "nPubInputs": 1, "nOutputs": 1, "nVars": 4}"#;
+    let (r1cs, wire_mapping) = load_r1cs_from_array::<Bn256>(json).unwrap();
+    assert_eq!((r1cs.num_inputs, r1cs.num_aux), (3, 1));
+    assert_eq!(r1cs.constraints[0].1, vec![(2, Fr::from_str("3").unwrap())]);
+    assert!(wire_mapping.is_none());
+}
+
+#[test]
+fn serialized_proof_round_trip() {
+    let mut proof = Proof::<Bn256, PlonkCsWidth4WithNextStepParams>::empty();
+    proof.n = 1023;
+    proof.num_inputs = 1;
+    proof.input_values = vec![Fr::from_str("5").unwrap()];
+    proof.wire_commitments = vec![G1Affine::one(); 4];
+    proof.grand_product_commitment = G1Affine::one();
+    proof.quotient_poly_commitments = vec![G1Affine::zero(); 4];
+    proof.wire_values_at_z = vec![Fr::from_str("1").unwrap(); 4];
+    proof.wire_values_at_z_omega = vec![Fr::from_str("2").unwrap()];
+    proof.permutation_polynomials_at_z = vec![Fr::from_str("3").unwrap(); 3];
+    proof.opening_at_z_proof = G1Affine::one();
+    proof.opening_at_z_omega_proof = G1Affine::one();
+
+    let (inputs, serialized_proof) = bellman_vk_codegen::serialize_proof(&proof);
+    let inputs = inputs.iter().map(ToString::to_string).collect_vec();
+    let serialized_proof = serialized_proof.iter().map(ToString::to_string).collect_vec();
+    assert_eq!(serialized_proof.len(), SERIALIZED_PROOF_LEN);
+    let loaded = load_proof_from_serialized(&inputs, &serialized_proof, proof.n).unwrap();
+
+    let (mut expected, mut actual) = (Vec::new(), Vec::new());
+    proof.write(&mut expected).unwrap();
+    loaded.write(&mut actual).unwrap();
+    assert_eq!(actual, expected);
+    assert!(load_proof_from_serialized(&inputs, &serialized_proof[1..], proof.n).is_err());
+    let hex_inputs = vec!["0x5".to_owned()];
+    let loaded = load_proof_from_serialized(&hex_inputs, &serialized_proof, proof.n).unwrap();
+    assert_eq!(loaded.input_values, proof.input_values);
+}
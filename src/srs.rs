@@ -0,0 +1,341 @@
+// Checks and conversions of universal setups (srs). A srs in monomial form holds tau^i * G1 for i < 2^power and
+// G2, tau * G2, the lagrange form holds L_i(tau) * G1 for the lagrange basis L_i of a domain of 2^power.
+// Both are written by bellman as a big endian u64 count and the uncompressed G1 points, then the same for G2.
+use crate::error::{Error, Result};
+use bellman_ce::{
+    kate_commitment::{Crs, CrsForLagrangeForm, CrsForMonomialForm},
+    multiexp::dense_multiexp,
+    pairing::{ff::PrimeField, CurveAffine, CurveProjective, EncodedPoint, Engine},
+    worker::Worker,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use rand::Rand;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::Mutex;
+
+// number of G1 points combined by one multiexp of `check_powers`
+const CHECK_CHUNK: usize = 1 << 20;
+
+// Checks that a point lies on the curve and in the prime order subgroup
+fn check_point<G: CurveAffine>(point: G, name: &str, index: usize) -> Result<()> {
+    if point.is_zero() {
+        return Err(Error::Format(format!("{} point {} is the point at infinity", name, index)));
+    }
+    let (x, y) = point.into_xy_unchecked();
+    G::from_xy_checked(x, y).map_err(|e| Error::Format(format!("{} point {} is invalid: {}", name, index, e)))?;
+    Ok(())
+}
+
+fn check_points<G: CurveAffine>(points: &[G], name: &str, worker: &Worker) -> Result<()> {
+    let error = Mutex::new(None);
+    worker.scope(points.len(), |scope, chunk| {
+        for (i, chunk_points) in points.chunks(chunk).enumerate() {
+            let error = &error;
+            scope.spawn(move |_| {
+                for (j, point) in chunk_points.iter().enumerate() {
+                    if let Err(e) = check_point(*point, name, i * chunk + j) {
+                        error.lock().expect("lock").get_or_insert(e);
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    match error.into_inner().expect("lock") {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+// With random r_i, e(sum r_i * g1[i], tau * G2) = e(sum r_i * g1[i + 1], G2) holds for all i at once only if
+// every G1 point is tau times the one before, except with negligible probability
+fn check_powers<E: Engine>(key_monomial_form: &Crs<E, CrsForMonomialForm>, worker: &Worker) -> Result<()> {
+    let g1 = &key_monomial_form.g1_bases;
+    let g2 = &key_monomial_form.g2_monomial_bases;
+    let rng = &mut rand::thread_rng();
+    let mut lhs = E::G1::zero();
+    let mut rhs = E::G1::zero();
+    for start in (0..g1.len() - 1).step_by(CHECK_CHUNK) {
+        let end = std::cmp::min(start + CHECK_CHUNK, g1.len() - 1);
+        let scalars = (start..end).map(|_| E::Fr::rand(rng).into_repr()).collect::<Vec<_>>();
+        lhs.add_assign(&dense_multiexp(worker, &g1[start..end], &scalars)?);
+        rhs.add_assign(&dense_multiexp(worker, &g1[start + 1..end + 1], &scalars)?);
+    }
+    if E::pairing(lhs.into_affine(), g2[1]) != E::pairing(rhs.into_affine(), g2[0]) {
+        return Err(Error::Format(
+            "the G1 points are not successive powers of the tau of the G2 points".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that every point is valid, that the srs starts from the generators and that its G1 points are
+/// successive powers of the tau of its G2 points.
+pub fn check_monomial_form<E: Engine>(key_monomial_form: &Crs<E, CrsForMonomialForm>) -> Result<()> {
+    let g1 = &key_monomial_form.g1_bases;
+    let g2 = &key_monomial_form.g2_monomial_bases;
+    if g1.is_empty() || g2.len() != 2 {
+        return Err(Error::Format(format!(
+            "the srs holds {} G1 and {} G2 points, expected at least 1 and 2",
+            g1.len(),
+            g2.len()
+        )));
+    }
+    let worker = Worker::new();
+    check_points(g1, "G1", &worker)?;
+    check_points(g2, "G2", &worker)?;
+    if g1[0] != E::G1Affine::one() || g2[0] != E::G2Affine::one() {
+        return Err(Error::Format("the srs does not start from the generators".to_owned()));
+    }
+    check_powers(key_monomial_form, &worker)
+}
+
+/// Checks that a srs in lagrange form is the one derived from `key_monomial_form` for its domain size.
+pub fn check_lagrange_form<E: Engine>(
+    key_monomial_form: &Crs<E, CrsForMonomialForm>,
+    key_lagrange_form: &Crs<E, CrsForLagrangeForm>,
+) -> Result<()> {
+    let size = key_lagrange_form.g1_bases.len();
+    if !size.is_power_of_two() {
+        return Err(Error::Format(format!(
+            "the lagrange form srs holds {} points, not a power of two",
+            size
+        )));
+    }
+    if size > key_monomial_form.g1_bases.len() {
+        return Err(Error::SrsTooSmall {
+            required: size,
+            available: key_monomial_form.g1_bases.len(),
+        });
+    }
+    let expected = Crs::<E, CrsForLagrangeForm>::from_powers(key_monomial_form, size, &Worker::new());
+    if let Some(index) = (0..size).find(|&i| expected.g1_bases[i] != key_lagrange_form.g1_bases[i]) {
+        return Err(Error::Format(format!(
+            "lagrange form G1 point {} does not match the monomial form srs",
+            index
+        )));
+    }
+    if expected.g2_monomial_bases != key_lagrange_form.g2_monomial_bases {
+        return Err(Error::Format(
+            "lagrange form G2 points do not match the monomial form srs".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Summary of a srs file, as printed by `plonkit srs info`.
+#[derive(Serialize)]
+pub struct SrsInfo {
+    /// "monomial" or "lagrange"
+    pub form: String,
+    pub size: usize,
+    /// None when the size is not a power of two
+    pub power: Option<u32>,
+    pub g2_size: usize,
+    /// sha256 of the whole file, in hex
+    pub sha256: String,
+}
+
+fn g1_size<E: Engine>() -> u64 {
+    <E::G1Affine as CurveAffine>::Uncompressed::size() as u64
+}
+
+fn g2_size<E: Engine>() -> u64 {
+    <E::G2Affine as CurveAffine>::Uncompressed::size() as u64
+}
+
+// Bytes taken by `count` points, counts come from the file so the sizes derived from them are checked
+fn points_len(count: u64, point_size: u64) -> Result<u64> {
+    count
+        .checked_mul(point_size)
+        .ok_or_else(|| Error::Format(format!("the srs holds too many points: {}", count)))
+}
+
+fn add_len(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b)
+        .ok_or_else(|| Error::Format("the srs holds too many points".to_owned()))
+}
+
+// 2^power, checked against the number of points of the source srs before shifting
+fn required_size(power: u32, available: usize) -> Result<usize> {
+    match 1usize.checked_shl(power) {
+        Some(required) if required <= available => Ok(required),
+        required => Err(Error::SrsTooSmall {
+            required: required.unwrap_or(usize::MAX),
+            available,
+        }),
+    }
+}
+
+fn read_first_g1<E: Engine, R: Read>(mut reader: R) -> Result<E::G1Affine> {
+    let mut encoded = <E::G1Affine as CurveAffine>::Uncompressed::empty();
+    reader.read_exact(encoded.as_mut())?;
+    encoded.into_affine().map_err(|e| Error::Format(format!("invalid G1 point: {}", e)))
+}
+
+/// Reads the counts and the first point of a srs file and hashes it, without loading the points. Both forms share
+/// one layout, only the monomial form starts from the generator.
+pub fn info<E: Engine, R: Read + Seek>(mut reader: R) -> Result<SrsInfo> {
+    let size = reader.read_u64::<BigEndian>()?;
+    let form = if size > 0 && read_first_g1::<E, _>(&mut reader)? == E::G1Affine::one() {
+        "monomial"
+    } else {
+        "lagrange"
+    };
+    let g2_offset = add_len(8, points_len(size, g1_size::<E>())?)?;
+    reader.seek(SeekFrom::Start(g2_offset))?;
+    let g2_count = reader.read_u64::<BigEndian>()?;
+    let file_size = add_len(add_len(g2_offset, 8)?, points_len(g2_count, g2_size::<E>())?)?;
+
+    reader.seek(SeekFrom::Start(0))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 20];
+    let mut read = 0u64;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        read += n as u64;
+    }
+    if read != file_size {
+        return Err(Error::Format(format!(
+            "the srs file has {} bytes, its counts need {}",
+            read, file_size
+        )));
+    }
+
+    Ok(SrsInfo {
+        form: form.to_owned(),
+        size: size as usize,
+        power: if size.is_power_of_two() {
+            Some(size.trailing_zeros())
+        } else {
+            None
+        },
+        g2_size: g2_count as usize,
+        sha256: hex::encode(hasher.finalize()),
+    })
+}
+
+/// Copies the first 2^power G1 points and the G2 points of a srs in monomial form, without loading the points.
+pub fn truncate<E: Engine, R: Read + Seek, W: Write>(mut reader: R, mut writer: W, power: u32) -> Result<()> {
+    let size = reader.read_u64::<BigEndian>()?;
+    let g2_offset = add_len(8, points_len(size, g1_size::<E>())?)?;
+    let required = required_size(power, size as usize)? as u64;
+    let first = read_first_g1::<E, _>(&mut reader)?;
+    if first != E::G1Affine::one() {
+        return Err(Error::Format("the srs is not in monomial form".to_owned()));
+    }
+
+    writer.write_u64::<BigEndian>(required)?;
+    reader.seek(SeekFrom::Start(8))?;
+    let g1_bytes = points_len(required, g1_size::<E>())?;
+    if std::io::copy(&mut (&mut reader).take(g1_bytes), &mut writer)? != g1_bytes {
+        return Err(Error::Format("the srs file is truncated".to_owned()));
+    }
+    reader.seek(SeekFrom::Start(g2_offset))?;
+    let g2_count = reader.read_u64::<BigEndian>()?;
+    writer.write_u64::<BigEndian>(g2_count)?;
+    let g2_bytes = points_len(g2_count, g2_size::<E>())?;
+    if std::io::copy(&mut reader.take(g2_bytes), &mut writer)? != g2_bytes {
+        return Err(Error::Format("the srs file is truncated".to_owned()));
+    }
+    Ok(())
+}
+
+/// Derives the srs in lagrange form for a domain of 2^power, no circuit needed.
+pub fn to_lagrange_form<E: Engine>(key_monomial_form: &Crs<E, CrsForMonomialForm>, power: u32) -> Result<Crs<E, CrsForLagrangeForm>> {
+    let size = required_size(power, key_monomial_form.g1_bases.len())?;
+    Ok(Crs::<E, CrsForLagrangeForm>::from_powers(key_monomial_form, size, &Worker::new()))
+}
+
+#[test]
+fn monomial_form() {
+    use bellman_ce::pairing::bn256::{Bn256, G1Affine};
+
+    let srs = Crs::<Bn256, CrsForMonomialForm>::crs_42(16, &Worker::new());
+    check_monomial_form(&srs).unwrap();
+
+    let mut g1_bases = srs.g1_bases.to_vec();
+    g1_bases.swap(3, 4);
+    let swapped = Crs::<Bn256, CrsForMonomialForm>::new(g1_bases, srs.g2_monomial_bases.to_vec());
+    let err = check_monomial_form(&swapped).err().unwrap();
+    assert!(err.to_string().contains("successive powers"));
+
+    let mut g1_bases = srs.g1_bases.to_vec();
+    g1_bases[5] = G1Affine::zero();
+    let infinity = Crs::<Bn256, CrsForMonomialForm>::new(g1_bases, srs.g2_monomial_bases.to_vec());
+    let err = check_monomial_form(&infinity).err().unwrap();
+    assert!(err.to_string().contains("G1 point 5 is the point at infinity"));
+}
+
+#[test]
+fn lagrange_form() {
+    use bellman_ce::pairing::bn256::Bn256;
+
+    let worker = Worker::new();
+    let srs = Crs::<Bn256, CrsForMonomialForm>::crs_42(16, &worker);
+    let lagrange = Crs::<Bn256, CrsForLagrangeForm>::from_powers(&srs, 8, &worker);
+    check_lagrange_form(&srs, &lagrange).unwrap();
+
+    let mut g1_bases = lagrange.g1_bases.to_vec();
+    g1_bases.swap(0, 1);
+    let swapped = Crs::<Bn256, CrsForLagrangeForm>::new(g1_bases, lagrange.g2_monomial_bases.to_vec());
+    let err = check_lagrange_form(&srs, &swapped).err().unwrap();
+    assert!(err.to_string().contains("G1 point 0"));
+}
+
+#[test]
+fn info_and_truncate() {
+    use bellman_ce::pairing::bn256::Bn256;
+    use std::io::Cursor;
+
+    let worker = Worker::new();
+    let srs = Crs::<Bn256, CrsForMonomialForm>::crs_42(16, &worker);
+    let mut data = Vec::new();
+    srs.write(&mut data).unwrap();
+
+    let srs_info = info::<Bn256, _>(Cursor::new(&data)).unwrap();
+    assert_eq!(srs_info.form, "monomial");
+    assert_eq!((srs_info.size, srs_info.power, srs_info.g2_size), (16, Some(4), 2));
+    assert_eq!(srs_info.sha256, hex::encode(Sha256::digest(&data)));
+
+    let mut truncated = Vec::new();
+    truncate::<Bn256, _, _>(Cursor::new(&data), &mut truncated, 3).unwrap();
+    let mut expected = Vec::new();
+    Crs::<Bn256, CrsForMonomialForm>::crs_42(8, &worker).write(&mut expected).unwrap();
+    assert_eq!(truncated, expected);
+    let err = truncate::<Bn256, _, _>(Cursor::new(&data), Vec::new(), 5).err().unwrap();
+    assert!(matches!(
+        err,
+        Error::SrsTooSmall {
+            required: 32,
+            available: 16
+        }
+    ));
+    let err = truncate::<Bn256, _, _>(Cursor::new(&data), Vec::new(), 64).err().unwrap();
+    assert!(matches!(err, Error::SrsTooSmall { available: 16, .. }));
+    assert!(matches!(to_lagrange_form(&srs, 100), Err(Error::SrsTooSmall { available: 16, .. })));
+
+    let mut lagrange = Vec::new();
+    to_lagrange_form(&srs, 3).unwrap().write(&mut lagrange).unwrap();
+    let lagrange_info = info::<Bn256, _>(Cursor::new(&lagrange)).unwrap();
+    assert_eq!(lagrange_info.form, "lagrange");
+    assert_eq!(lagrange_info.power, Some(3));
+    let err = truncate::<Bn256, _, _>(Cursor::new(&lagrange), Vec::new(), 2).err().unwrap();
+    assert!(err.to_string().contains("not in monomial form"));
+
+    let mut huge = data.clone();
+    huge[..8].copy_from_slice(&u64::MAX.to_be_bytes());
+    let err = info::<Bn256, _>(Cursor::new(&huge)).err().unwrap();
+    assert!(matches!(err, Error::Format(_)));
+    let err = truncate::<Bn256, _, _>(Cursor::new(&huge), Vec::new(), 3).err().unwrap();
+    assert!(matches!(err, Error::Format(_)));
+
+    let cut = &data[..8 + 4 * g1_size::<Bn256>() as usize];
+    let err = truncate::<Bn256, _, _>(Cursor::new(cut), Vec::new(), 3).err().unwrap();
+    assert!(err.to_string().contains("truncated"));
+}
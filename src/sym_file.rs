@@ -0,0 +1,122 @@
+// A circom .sym file has one `label_index,wire_index,component_index,name` line per signal,
+// where wire_index is -1 for signals optimized out of the r1cs.
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
+#[derive(Clone, Default)]
+pub struct SymbolTable {
+    labels: BTreeMap<usize, String>,
+    wires: BTreeMap<usize, String>,
+}
+
+impl SymbolTable {
+    pub fn label_name(&self, label: usize) -> Option<&str> {
+        self.labels.get(&label).map(String::as_str)
+    }
+
+    /// Name of a wire. Several signals can share one wire, the first one declared wins.
+    pub fn wire_name(&self, wire: usize) -> Option<&str> {
+        self.wires.get(&wire).map(String::as_str)
+    }
+
+    pub fn num_labels(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn wires(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.wires.iter().map(|(wire, name)| (*wire, name.as_str()))
+    }
+}
+
+/// Component a signal belongs to, e.g. `main.hasher.mix[3]` for `main.hasher.mix[3].out`.
+pub fn component_of(signal: &str) -> &str {
+    match signal.rfind('.') {
+        Some(pos) => &signal[..pos],
+        None => signal,
+    }
+}
+
+/// The component and all its ancestors, innermost first: `main.hasher.mix[3]`, `main.hasher`, `main`.
+pub fn component_ancestors(component: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(component).chain(component.rmatch_indices('.').map(move |(pos, _)| &component[..pos]))
+}
+
+/// Innermost component containing all of the signals.
+pub fn common_component<'a, I: IntoIterator<Item = &'a str>>(signals: I) -> Option<String> {
+    let mut common: Option<Vec<&str>> = None;
+    for signal in signals {
+        let segments = component_of(signal).split('.');
+        common = Some(match common {
+            None => segments.collect(),
+            Some(prefix) => prefix
+                .into_iter()
+                .zip(segments)
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    common.filter(|segments| !segments.is_empty()).map(|segments| segments.join("."))
+}
+
+fn parse_index(field: Option<&str>, line_no: usize) -> Result<i64> {
+    field
+        .and_then(|f| f.trim().parse().ok())
+        .ok_or_else(|| Error::Format(format!("Invalid symbol at line {}", line_no + 1)))
+}
+
+pub fn from_reader<R: BufRead>(reader: R) -> Result<SymbolTable> {
+    let mut table = SymbolTable::default();
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line.splitn(4, ',');
+        let label = parse_index(fields.next(), line_no)?;
+        let wire = parse_index(fields.next(), line_no)?;
+        let _component = parse_index(fields.next(), line_no)?;
+        let name = fields
+            .next()
+            .ok_or_else(|| Error::Format(format!("Missing symbol name at line {}", line_no + 1)))?
+            .trim()
+            .to_owned();
+        if label < 0 {
+            return Err(Error::Format(format!("Invalid label index at line {}", line_no + 1)));
+        }
+        if wire >= 0 {
+            table.wires.entry(wire as usize).or_insert_with(|| name.clone());
+        }
+        table.labels.insert(label as usize, name);
+    }
+    Ok(table)
+}
+
+#[test]
+fn sample() {
+    let data = "1,1,0,main.out\n2,2,0,main.in[0]\n3,-1,0,main.in[1]\n4,1,1,main.hasher.out\n\n";
+    let table = from_reader(data.as_bytes()).unwrap();
+    assert_eq!(table.num_labels(), 4);
+    assert_eq!(table.label_name(3), Some("main.in[1]"));
+    assert_eq!(table.wire_name(1), Some("main.out"));
+    assert_eq!(table.wire_name(2), Some("main.in[0]"));
+    assert_eq!(table.wire_name(3), None);
+
+    let err = from_reader("1,x,0,main.out\n".as_bytes()).err().unwrap();
+    assert!(matches!(err, Error::Format(_)));
+}
+
+#[test]
+fn components() {
+    assert_eq!(component_of("main.hasher.mix[3].out"), "main.hasher.mix[3]");
+    assert_eq!(
+        component_ancestors("main.hasher.mix[3]").collect::<Vec<_>>(),
+        vec!["main.hasher.mix[3]", "main.hasher", "main"]
+    );
+    assert_eq!(
+        common_component(vec!["main.hasher.out", "main.hasher.mix[3].in[0]"]),
+        Some("main.hasher".to_owned())
+    );
+    assert_eq!(common_component(vec!["main.a", "other.b"]), None);
+}
@@ -10,6 +10,12 @@ use bellman_ce::SynthesisError;
 pub struct ConstraintStat {
     pub name: String,
     pub num_gates: usize,
+    /// Number of terms in A, B and C together
+    pub lc_width: usize,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub variant: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub signals: Vec<String>,
 }
 
 pub struct TranspilerWrapper<E: Engine, P: PlonkConstraintSystemParams<E>> {
@@ -96,10 +102,17 @@ impl<E: Engine, P: PlonkConstraintSystemParams<E>> bellman_ce::ConstraintSystem<
     {
         let num_gates_before = self.inner.num_gates();
         let name_ = ann().into();
-        self.inner.enforce(|| name_.clone(), a, b, c);
+        let a = a(bellman_ce::LinearCombination::zero());
+        let b = b(bellman_ce::LinearCombination::zero());
+        let c = c(bellman_ce::LinearCombination::zero());
+        let lc_width = a.as_ref().len() + b.as_ref().len() + c.as_ref().len();
+        self.inner.enforce(|| name_.clone(), |_| a, |_| b, |_| c);
         self.constraint_stats.push(ConstraintStat {
             name: name_,
             num_gates: self.inner.num_gates() - num_gates_before,
+            lc_width,
+            variant: String::new(),
+            signals: Vec::new(),
         });
     }
 
@@ -122,12 +135,10 @@ impl<E: Engine, P: PlonkConstraintSystemParams<E>> bellman_ce::ConstraintSystem<
 
 pub fn transpile_with_gates_count<E: Engine, C: bellman_ce::Circuit<E>>(
     circuit: C,
-) -> Result<(usize, Vec<(usize, TranspilationVariant)>), SynthesisError> {
+) -> crate::Result<(usize, Vec<(usize, TranspilationVariant)>)> {
     let mut transpiler = TranspilerWrapper::<E, PlonkCsWidth4WithNextStepParams>::new();
 
-    circuit
-        .synthesize(&mut transpiler)
-        .expect("sythesize into traspilation must succeed");
+    circuit.synthesize(&mut transpiler)?;
 
     let (n, hints) = transpiler.into_hints_and_num_gates();
 
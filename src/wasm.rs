@@ -0,0 +1,98 @@
+// JavaScript bindings for browsers and node, built with
+//   wasm-pack build --target web -- --no-default-features --features wasm
+// Only bn256 is supported, like the solidity verifier. Proving runs on one thread, it suits small circuits only.
+use bellman_ce::pairing::bn256::Bn256;
+use wasm_bindgen::prelude::*;
+
+use crate::api::{Prover, Verifier};
+use crate::reader;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProveResult {
+    proof: Vec<u8>,
+    public_inputs: Vec<String>,
+    serialized_proof: Vec<String>,
+}
+
+fn js_error<E: std::fmt::Display>(e: E) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+/// Verifies a proof.bin against a vk.bin.
+#[wasm_bindgen]
+pub fn verify(vk: &[u8], proof: &[u8]) -> Result<bool, JsValue> {
+    let vk = reader::load_verification_key_from_reader::<Bn256, _>(vk).map_err(js_error)?;
+    let proof = reader::load_proof_from_reader::<Bn256, _>(proof).map_err(js_error)?;
+    Verifier::from_vk(vk).verify(&proof).map_err(js_error)
+}
+
+/// Verifies a proof given as the contents of public.json and proof.json against a vk.bin.
+#[wasm_bindgen(js_name = verifySerialized)]
+pub fn verify_serialized(vk: &[u8], inputs: JsValue, proof: JsValue) -> Result<bool, JsValue> {
+    let vk = reader::load_verification_key_from_reader::<Bn256, _>(vk).map_err(js_error)?;
+    let inputs: Vec<String> = inputs.into_serde().map_err(js_error)?;
+    let proof: Vec<String> = proof.into_serde().map_err(js_error)?;
+    let proof = reader::load_proof_from_serialized(&inputs, &proof, vk.n).map_err(js_error)?;
+    Verifier::from_vk(vk).verify(&proof).map_err(js_error)
+}
+
+/// Proves a witness, in the .wtns or JSON format, for a circuit in the .r1cs or JSON format.
+/// Resolves to `{ proof, publicInputs, serializedProof }`, `proof` holds the bytes of a proof.bin.
+#[wasm_bindgen]
+pub fn prove(circuit: &[u8], srs_monomial: &[u8], witness: &[u8]) -> Result<JsValue, JsValue> {
+    let (r1cs, wire_mapping) = reader::load_r1cs_from_array::<Bn256>(circuit).map_err(js_error)?;
+    let witness = if witness.starts_with(b"wtns") {
+        reader::load_witness_from_array::<Bn256>(witness.to_vec())
+    } else {
+        reader::load_witness_from_json_reader::<Bn256, _>(witness)
+    }
+    .map_err(js_error)?;
+    let prover = Prover::builder()
+        .circuit(r1cs, wire_mapping)
+        .srs_monomial(reader::load_key_monomial_form_from_reader(srs_monomial).map_err(js_error)?)
+        .build()
+        .map_err(js_error)?;
+    let artifacts = prover.prove(witness).map_err(js_error)?;
+    let mut proof = Vec::new();
+    artifacts.proof.write(&mut proof).map_err(js_error)?;
+    let result = ProveResult {
+        proof,
+        public_inputs: artifacts.public_inputs,
+        serialized_proof: artifacts.serialized_proof.unwrap_or_default(),
+    };
+    JsValue::from_serde(&result).map_err(js_error)
+}
+
+// run with `wasm-pack test --headless --firefox -- --no-default-features --features wasm`
+#[cfg(all(test, target_arch = "wasm32"))]
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(all(test, target_arch = "wasm32"))]
+#[wasm_bindgen_test::wasm_bindgen_test]
+fn prove_and_verify() {
+    use bellman_ce::kate_commitment::{Crs, CrsForMonomialForm};
+    // out <== a * b, with a = 3 and b = 4
+    let circuit = br#"{"constraints": [[{"2": "1"}, {"3": "1"}, {"1": "1"}]], "nPubInputs": 0, "nOutputs": 1, "nVars": 4}"#;
+    let witness = br#"["1", "12", "3", "4"]"#;
+    let srs = Crs::<Bn256, CrsForMonomialForm>::dummy_crs(256);
+    let mut srs_bytes = Vec::new();
+    srs.write(&mut srs_bytes).unwrap();
+
+    let result: ProveResult = prove(circuit, &srs_bytes, witness).unwrap().into_serde().unwrap();
+    assert_eq!(result.public_inputs, vec!["12".to_owned()]);
+
+    let (r1cs, wire_mapping) = reader::load_r1cs_from_array::<Bn256>(circuit).unwrap();
+    let prover = Prover::builder().circuit(r1cs, wire_mapping).srs_monomial(srs).build().unwrap();
+    let mut vk = Vec::new();
+    prover.verification_key().unwrap().write(&mut vk).unwrap();
+
+    assert!(verify(&vk, &result.proof).unwrap());
+    let inputs = JsValue::from_serde(&result.public_inputs).unwrap();
+    let serialized_proof = JsValue::from_serde(&result.serialized_proof).unwrap();
+    assert!(verify_serialized(&vk, inputs, serialized_proof).unwrap());
+    let wrong_inputs = JsValue::from_serde(&["13"]).unwrap();
+    let serialized_proof = JsValue::from_serde(&result.serialized_proof).unwrap();
+    assert!(!verify_serialized(&vk, wrong_inputs, serialized_proof).unwrap());
+    assert!(verify(&vk, &result.proof[1..]).is_err());
+}
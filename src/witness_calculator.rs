@@ -0,0 +1,394 @@
+// Runs the witness calculator compiled by circom to wasm, a port of the witness_calculator.js
+// shipped with circom 0.5 and circom 2. The two generations differ in how they exchange field
+// elements: circom 0.5 shares its linear memory and may keep elements in Montgomery form,
+// circom 2 exposes a small shared buffer accessed through exported functions.
+use anyhow::{bail, format_err};
+use bellman_ce::pairing::{ff::PrimeField, Engine};
+use itertools::Itertools;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{Num, One, Zero};
+use std::collections::BTreeMap;
+use std::io::Read;
+use wasmer::{imports, Exports, ExternType, Function, Instance, Memory, Module, RuntimeError, Store};
+
+use crate::curve::Curve;
+use crate::utils::repr_to_big;
+
+pub type Inputs = BTreeMap<String, Vec<BigInt>>;
+
+#[derive(Debug)]
+struct CircomRuntimeError(String);
+
+impl std::fmt::Display for CircomRuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for CircomRuntimeError {}
+
+fn raise(msg: String) -> ! {
+    RuntimeError::raise(Box::new(CircomRuntimeError(msg)))
+}
+
+pub struct WitnessCalculator {
+    instance: Instance,
+    // only circom 0.5 calculators import their memory
+    memory: Option<Memory>,
+    n32: usize,
+    prime: BigUint,
+}
+
+impl WitnessCalculator {
+    pub fn from_file(filename: &str) -> Result<Self, anyhow::Error> {
+        let wasm = std::fs::read(filename).map_err(|e| format_err!("Failed to read wasm file {}, err: {}", filename, e))?;
+        Self::from_bytes(&wasm)
+    }
+
+    pub fn from_bytes(wasm: &[u8]) -> Result<Self, anyhow::Error> {
+        let store = Store::default();
+        let module = Module::new(&store, wasm)?;
+
+        let mut import_object = imports! {
+            "runtime" => {
+                // circom 0.5
+                "error" => Function::new_native(&store, |code: i32, _pstr: i32, a: i32, b: i32, c: i32, d: i32| {
+                    raise(format!("circom runtime error {} ({}, {}, {}, {})", code, a, b, c, d))
+                }),
+                "logSetSignal" => Function::new_native(&store, |_signal: i32, _p_val: i32| {}),
+                "logGetSignal" => Function::new_native(&store, |_signal: i32, _p_val: i32| {}),
+                "logFinishComponent" => Function::new_native(&store, |_c_idx: i32| {}),
+                "logStartComponent" => Function::new_native(&store, |_c_idx: i32| {}),
+                "log" => Function::new_native(&store, |_t: i32| {}),
+                // circom 2
+                "exceptionHandler" => Function::new_native(&store, |code: i32| {
+                    let msg = match code {
+                        1 => "signal not found",
+                        2 => "too many signals set",
+                        3 => "signal already set",
+                        4 => "assert failed",
+                        5 => "not enough memory",
+                        6 => "input signal array access exceeds the size",
+                        _ => "unknown error",
+                    };
+                    raise(format!("circom runtime error {}: {}", code, msg))
+                }),
+                "showSharedRWMemory" => Function::new_native(&store, || {}),
+                "printErrorMessage" => Function::new_native(&store, || {}),
+                "writeBufferMessage" => Function::new_native(&store, || {}),
+            }
+        };
+
+        let mut memory = None;
+        for import in module.imports() {
+            if let ExternType::Memory(ty) = import.ty() {
+                let mem = Memory::new(&store, *ty)?;
+                let mut namespace = Exports::new();
+                namespace.insert(import.name(), mem.clone());
+                import_object.register(import.module(), namespace);
+                memory = Some(mem);
+            }
+        }
+
+        let instance = Instance::new(&module, &import_object)?;
+        let mut calculator = WitnessCalculator {
+            instance,
+            memory,
+            n32: 0,
+            prime: BigUint::zero(),
+        };
+        if calculator.is_circom2() {
+            calculator.n32 = calculator
+                .instance
+                .exports
+                .get_native_function::<(), i32>("getFieldNumLen32")?
+                .call()? as usize;
+            calculator.instance.exports.get_native_function::<(), ()>("getRawPrime")?.call()?;
+            calculator.prime = calculator.read_shared_rw_memory()?;
+        } else {
+            let fr_len = calculator.instance.exports.get_native_function::<(), i32>("getFrLen")?.call()?;
+            calculator.n32 = (fr_len as usize >> 2) - 2;
+            let p_raw_prime = calculator.instance.exports.get_native_function::<(), i32>("getPRawPrime")?.call()?;
+            calculator.prime = calculator.read_words(p_raw_prime as usize >> 2)?;
+        }
+        Ok(calculator)
+    }
+
+    fn is_circom2(&self) -> bool {
+        self.instance.exports.get_function("getFieldNumLen32").is_ok()
+    }
+
+    /// Curve the witness calculator is compiled for, `None` for a prime plonkit does not support.
+    pub fn curve(&self) -> Option<Curve> {
+        let mut prime = self.prime.to_bytes_le();
+        prime.resize(self.n32 * 4, 0);
+        Curve::from_prime(&prime)
+    }
+
+    pub fn calculate_witness<E: Engine>(&mut self, inputs: &Inputs) -> Result<Vec<E::Fr>, anyhow::Error> {
+        if self.prime.to_str_radix(10) != repr_to_big(E::Fr::char()) {
+            bail!("the witness calculator is compiled for another curve");
+        }
+        let witness = if self.is_circom2() {
+            self.calculate_witness_circom2(inputs)?
+        } else {
+            self.calculate_witness_circom05(inputs)?
+        };
+        witness
+            .iter()
+            .map(|w| E::Fr::from_str(&w.to_str_radix(10)).ok_or_else(|| format_err!("invalid witness value {}", w)))
+            .collect()
+    }
+
+    fn calculate_witness_circom2(&mut self, inputs: &Inputs) -> Result<Vec<BigUint>, anyhow::Error> {
+        let exports = &self.instance.exports;
+        exports.get_native_function::<i32, ()>("init")?.call(1)?;
+        let get_input_signal_size = exports.get_native_function::<(i32, i32), i32>("getInputSignalSize")?;
+        let set_input_signal = exports.get_native_function::<(i32, i32, i32), ()>("setInputSignal")?;
+        for (name, values) in inputs {
+            let (h_msb, h_lsb) = fnv_hash(name);
+            let signal_size = get_input_signal_size.call(h_msb, h_lsb)?;
+            if signal_size < 0 {
+                bail!("signal {} not found", name);
+            }
+            if values.len() != signal_size as usize {
+                bail!("input signal {} expects {} values, got {}", name, signal_size, values.len());
+            }
+            for (i, value) in values.iter().enumerate() {
+                self.write_shared_rw_memory(&self.reduce(value))?;
+                set_input_signal.call(h_msb, h_lsb, i as i32)?;
+            }
+        }
+
+        let witness_size = exports.get_native_function::<(), i32>("getWitnessSize")?.call()?;
+        let get_witness = exports.get_native_function::<i32, ()>("getWitness")?;
+        let mut witness = Vec::with_capacity(witness_size as usize);
+        for i in 0..witness_size {
+            get_witness.call(i)?;
+            witness.push(self.read_shared_rw_memory()?);
+        }
+        Ok(witness)
+    }
+
+    fn calculate_witness_circom05(&mut self, inputs: &Inputs) -> Result<Vec<BigUint>, anyhow::Error> {
+        // word 0 of the memory is the free pointer, restored on every path so that the calculator can be reused
+        let old_free_pos = self.read_word(0)?;
+        let witness = self.run_circom05(inputs);
+        self.write_word(0, old_free_pos)?;
+        witness
+    }
+
+    fn run_circom05(&mut self, inputs: &Inputs) -> Result<Vec<BigUint>, anyhow::Error> {
+        let exports = &self.instance.exports;
+        exports.get_native_function::<i32, ()>("init")?.call(1)?;
+        let p_sig_offset = self.alloc(8)?;
+        let p_fr = self.alloc(self.n32 * 4 + 8)?;
+
+        let get_signal_offset32 = exports.get_native_function::<(i32, i32, i32, i32), ()>("getSignalOffset32")?;
+        // points to the sizes of a signal, the total number of values first; checked when the module exports it
+        let get_signal_sizes32 = exports.get_native_function::<(i32, i32, i32, i32), ()>("getSignalSizes32").ok();
+        let set_signal = exports.get_native_function::<(i32, i32, i32, i32), ()>("setSignal")?;
+        for (name, values) in inputs {
+            let (h_msb, h_lsb) = fnv_hash(name);
+            if let Some(get_signal_sizes32) = &get_signal_sizes32 {
+                get_signal_sizes32.call(p_sig_offset as i32, 0, h_msb, h_lsb)?;
+                let p_sizes = self.read_word(p_sig_offset >> 2)?;
+                let signal_size = self.read_word(p_sizes as usize >> 2)?;
+                if values.len() != signal_size as usize {
+                    bail!("input signal {} expects {} values, got {}", name, signal_size, values.len());
+                }
+            }
+            get_signal_offset32.call(p_sig_offset as i32, 0, h_msb, h_lsb)?;
+            let sig_offset = self.read_word(p_sig_offset >> 2)?;
+            for (i, value) in values.iter().enumerate() {
+                self.set_fr(p_fr, &self.reduce(value))?;
+                set_signal.call(0, 0, (sig_offset as usize + i) as i32, p_fr as i32)?;
+            }
+        }
+
+        let n_vars = exports.get_native_function::<(), i32>("getNVars")?.call()?;
+        let get_p_witness = exports.get_native_function::<i32, i32>("getPWitness")?;
+        let mut witness = Vec::with_capacity(n_vars as usize);
+        for i in 0..n_vars {
+            let p_witness = get_p_witness.call(i)?;
+            witness.push(self.get_fr(p_witness as usize)?);
+        }
+        Ok(witness)
+    }
+
+    fn reduce(&self, value: &BigInt) -> BigUint {
+        let prime = BigInt::from_biguint(Sign::Plus, self.prime.clone());
+        let reduced = ((value % &prime) + &prime) % &prime;
+        reduced.to_biguint().expect("reduced value is positive")
+    }
+
+    fn read_shared_rw_memory(&self) -> Result<BigUint, anyhow::Error> {
+        let read = self.instance.exports.get_native_function::<i32, i32>("readSharedRWMemory")?;
+        let words = (0..self.n32)
+            .map(|j| read.call(j as i32).map(|w| w as u32))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(from_words(&words))
+    }
+
+    fn write_shared_rw_memory(&self, value: &BigUint) -> Result<(), anyhow::Error> {
+        let write = self.instance.exports.get_native_function::<(i32, i32), ()>("writeSharedRWMemory")?;
+        for (j, word) in to_words(value, self.n32).into_iter().enumerate() {
+            write.call(j as i32, word as i32)?;
+        }
+        Ok(())
+    }
+
+    fn memory(&self) -> Result<&Memory, anyhow::Error> {
+        self.memory
+            .as_ref()
+            .ok_or_else(|| format_err!("the witness calculator has no imported memory"))
+    }
+
+    fn read_word(&self, index: usize) -> Result<u32, anyhow::Error> {
+        let view = self.memory()?.view::<u32>();
+        Ok(view.get(index).ok_or_else(|| format_err!("memory access out of bounds"))?.get())
+    }
+
+    fn write_word(&self, index: usize, value: u32) -> Result<(), anyhow::Error> {
+        let view = self.memory()?.view::<u32>();
+        view.get(index)
+            .ok_or_else(|| format_err!("memory access out of bounds"))?
+            .set(value);
+        Ok(())
+    }
+
+    fn read_words(&self, index: usize) -> Result<BigUint, anyhow::Error> {
+        let words = (0..self.n32).map(|i| self.read_word(index + i)).collect::<Result<Vec<_>, _>>()?;
+        Ok(from_words(&words))
+    }
+
+    fn alloc(&self, size: usize) -> Result<usize, anyhow::Error> {
+        let p = self.read_word(0)? as usize;
+        self.write_word(0, (p + size) as u32)?;
+        Ok(p)
+    }
+
+    // An element is either short, a signed 32 bit integer in the first word, or long, `n32` words
+    // after a flags word telling whether it is in Montgomery form.
+    fn get_fr(&self, p: usize) -> Result<BigUint, anyhow::Error> {
+        let idx = p >> 2;
+        let flags = self.read_word(idx + 1)?;
+        if flags & 0x8000_0000 != 0 {
+            let value = self.read_words(idx + 2)?;
+            if flags & 0x4000_0000 != 0 {
+                let r = BigUint::one() << (32 * self.n32);
+                let r_inv = r.modpow(&(&self.prime - BigUint::from(2u32)), &self.prime);
+                Ok(value * r_inv % &self.prime)
+            } else {
+                Ok(value)
+            }
+        } else {
+            let short = self.read_word(idx)?;
+            if short & 0x8000_0000 != 0 {
+                Ok(&self.prime - (BigUint::one() << 32) + BigUint::from(short))
+            } else {
+                Ok(BigUint::from(short))
+            }
+        }
+    }
+
+    fn set_fr(&self, p: usize, value: &BigUint) -> Result<(), anyhow::Error> {
+        let idx = p >> 2;
+        if *value < BigUint::from(0x8000_0000u32) {
+            self.write_word(idx, to_words(value, 1)[0])?;
+            self.write_word(idx + 1, 0)?;
+        } else {
+            self.write_word(idx, 0)?;
+            self.write_word(idx + 1, 0x8000_0000)?;
+            for (i, word) in to_words(value, self.n32).into_iter().enumerate() {
+                self.write_word(idx + 2 + i, word)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 64 bit FNV-1a hash of a signal name, split in its high and low halves.
+fn fnv_hash(name: &str) -> (i32, i32) {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in name.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    ((hash >> 32) as u32 as i32, hash as u32 as i32)
+}
+
+// little endian 32 bit words
+fn from_words(words: &[u32]) -> BigUint {
+    BigUint::from_bytes_le(&words.iter().flat_map(|w| w.to_le_bytes().to_vec()).collect_vec())
+}
+
+fn to_words(value: &BigUint, n32: usize) -> Vec<u32> {
+    let mut bytes = value.to_bytes_le();
+    bytes.resize(n32 * 4, 0);
+    bytes
+        .chunks(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect_vec()
+}
+
+fn flatten_input(value: &serde_json::Value, result: &mut Vec<BigInt>) -> Result<(), anyhow::Error> {
+    match value {
+        serde_json::Value::Array(values) => {
+            for v in values {
+                flatten_input(v, result)?;
+            }
+        }
+        serde_json::Value::Number(n) => result.push(n.to_string().parse()?),
+        serde_json::Value::String(s) => result.push(match s.strip_prefix("0x") {
+            Some(hex) => BigInt::from_str_radix(hex, 16)?,
+            None => s.parse()?,
+        }),
+        _ => bail!("invalid input value {}", value),
+    }
+    Ok(())
+}
+
+/// Reads circuit inputs in the input.json format of circom, nested arrays are flattened.
+pub fn load_inputs_from_json<R: Read>(reader: R) -> Result<Inputs, anyhow::Error> {
+    let json: BTreeMap<String, serde_json::Value> = serde_json::from_reader(reader)?;
+    json.iter()
+        .map(|(name, value)| {
+            let mut values = Vec::new();
+            flatten_input(value, &mut values)?;
+            Ok((name.clone(), values))
+        })
+        .collect()
+}
+
+#[test]
+fn signal_hash() {
+    // fnvHash("in") of witness_calculator.js: "08b73807b55c4bbe"
+    assert_eq!(fnv_hash("in"), (0x08b7_3807, 0xb55c_4bbe_u32 as i32));
+}
+
+// The fixtures implement `out <== a * b` with the interfaces of circom 2 and circom 0.5, see their .wat sources
+#[test]
+fn multiplier() {
+    use bellman_ce::pairing::bn256::Bn256;
+
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/test/circuits/multiplier/");
+    let inputs = load_inputs_from_json(std::fs::File::open(format!("{}input.json", dir)).unwrap()).unwrap();
+    let expected = crate::reader::load_witness_from_file::<Bn256>(&format!("{}witness.wtns", dir)).unwrap();
+    let mut unknown = inputs.clone();
+    unknown.insert("c".to_owned(), vec![BigInt::one()]);
+    for wasm in &["circom2.wasm", "circom05.wasm"] {
+        let mut calculator = WitnessCalculator::from_file(&format!("{}{}", dir, wasm)).unwrap();
+        assert_eq!(calculator.curve(), Some(Curve::Bn256));
+        assert_eq!(calculator.calculate_witness::<Bn256>(&inputs).unwrap(), expected);
+        // the calculator can be reused
+        assert_eq!(calculator.calculate_witness::<Bn256>(&inputs).unwrap(), expected);
+        assert!(calculator.calculate_witness::<Bn256>(&unknown).is_err());
+        let mut too_long = inputs.clone();
+        too_long.insert("a".to_owned(), vec![BigInt::one(), BigInt::one()]);
+        let err = calculator.calculate_witness::<Bn256>(&too_long).err().unwrap();
+        assert!(err.to_string().contains("expects 1 values, got 2"));
+        // failures leave the calculator usable
+        assert_eq!(calculator.calculate_witness::<Bn256>(&inputs).unwrap(), expected);
+    }
+}
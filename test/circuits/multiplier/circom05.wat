@@ -0,0 +1,165 @@
+;; Hand-written witness calculator of `out <== a * b` over bn128, with the interface circom 0.5 compiles to:
+;; the memory is imported and word 0 is its free pointer. Field elements are 10 words, a signed 32 bit short
+;; value, flags and a long value of 8 words, normal or in montgomery form. Signals are one, out, a, b from 384,
+;; one and out are kept in montgomery form.
+(module
+  (import "env" "memory" (memory 1))
+  (import "runtime" "error" (func $error (param i32 i32 i32 i32 i32 i32)))
+  (global $inputs (mut i32) (i32.const 0))
+  (data (i32.const 0) "\00\04\00\00")
+  ;; constants, little endian 32 bit words
+  (data (i32.const 64) "\01\00\00\f0\93\f5\e1\43\91\70\b9\79\48\e8\33\28\5d\58\81\81\b6\45\50\b8\29\a0\31\e1\72\4e\64\30")
+  (data (i32.const 96) "\a7\6d\21\ae\45\e6\b8\1b\e3\59\5c\e3\b1\3a\fe\53\85\80\bb\53\3d\83\49\8c\a5\44\4e\7f\b1\d0\16\02")
+  ;; sizes of the scalar input signals, the total number of values first
+  (data (i32.const 352) "\01\00\00\00")
+  (data (i32.const 128) "\fb\ff\ff\4f\1c\34\96\ac\29\cd\60\9f\95\76\fc\36\2e\46\79\78\6f\a3\6e\66\2f\df\07\9a\c1\77\0a\0e")
+
+  ;; dst = src, n words
+  (func $copy (param $src i32) (param $dst i32) (param $n i32)
+    (local $i i32)
+    (block $done (loop $next
+      (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+      (i32.store (i32.add (local.get $dst) (i32.shl (local.get $i) (i32.const 2)))
+        (i32.load (i32.add (local.get $src) (i32.shl (local.get $i) (i32.const 2)))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br $next))))
+
+  ;; zeroes n words from dst
+  (func $zero (param $dst i32) (param $n i32)
+    (local $i i32)
+    (block $done (loop $next
+      (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+      (i32.store (i32.add (local.get $dst) (i32.shl (local.get $i) (i32.const 2))) (i32.const 0))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br $next))))
+
+  ;; 1 when a >= b, 8 words
+  (func $gte (param $a i32) (param $b i32) (result i32)
+    (local $i i32) (local $x i32) (local $y i32)
+    (local.set $i (i32.const 7))
+    (block $done (loop $next
+      (br_if $done (i32.lt_s (local.get $i) (i32.const 0)))
+      (local.set $x (i32.load (i32.add (local.get $a) (i32.shl (local.get $i) (i32.const 2)))))
+      (local.set $y (i32.load (i32.add (local.get $b) (i32.shl (local.get $i) (i32.const 2)))))
+      (if (i32.gt_u (local.get $x) (local.get $y)) (then (return (i32.const 1))))
+      (if (i32.lt_u (local.get $x) (local.get $y)) (then (return (i32.const 0))))
+      (local.set $i (i32.sub (local.get $i) (i32.const 1)))
+      (br $next)))
+    (i32.const 1))
+
+  ;; r = a - b, 8 words
+  (func $sub (param $a i32) (param $b i32) (param $r i32)
+    (local $i i32) (local $t i64) (local $borrow i64)
+    (block $done (loop $next
+      (br_if $done (i32.ge_u (local.get $i) (i32.const 8)))
+      (local.set $t (i64.sub (i64.sub
+        (i64.load32_u (i32.add (local.get $a) (i32.shl (local.get $i) (i32.const 2))))
+        (i64.load32_u (i32.add (local.get $b) (i32.shl (local.get $i) (i32.const 2)))))
+        (local.get $borrow)))
+      (i64.store32 (i32.add (local.get $r) (i32.shl (local.get $i) (i32.const 2))) (local.get $t))
+      (local.set $borrow (i64.extend_i32_u (i64.lt_s (local.get $t) (i64.const 0))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br $next))))
+
+  ;; r = x * y / 2^256 mod p, montgomery multiplication over 8 words, the scratch t is 10 words at 160
+  (func $montmul (param $x i32) (param $y i32) (param $r i32)
+    (local $i i32) (local $j i32) (local $c i64) (local $m i64) (local $t i64) (local $yi i64)
+    (call $zero (i32.const 160) (i32.const 10))
+    (block $outer_done (loop $outer
+      (br_if $outer_done (i32.ge_u (local.get $i) (i32.const 8)))
+      (local.set $yi (i64.load32_u (i32.add (local.get $y) (i32.shl (local.get $i) (i32.const 2)))))
+      (local.set $c (i64.const 0))
+      (local.set $j (i32.const 0))
+      (block $done (loop $next
+        (br_if $done (i32.ge_u (local.get $j) (i32.const 8)))
+        (local.set $t (i64.add (i64.add
+          (i64.load32_u (i32.add (i32.const 160) (i32.shl (local.get $j) (i32.const 2))))
+          (i64.mul (i64.load32_u (i32.add (local.get $x) (i32.shl (local.get $j) (i32.const 2)))) (local.get $yi)))
+          (local.get $c)))
+        (i64.store32 (i32.add (i32.const 160) (i32.shl (local.get $j) (i32.const 2))) (local.get $t))
+        (local.set $c (i64.shr_u (local.get $t) (i64.const 32)))
+        (local.set $j (i32.add (local.get $j) (i32.const 1)))
+        (br $next)))
+      (local.set $t (i64.add (i64.load32_u (i32.const 192)) (local.get $c)))
+      (i64.store32 (i32.const 192) (local.get $t))
+      (i64.store32 (i32.const 196) (i64.shr_u (local.get $t) (i64.const 32)))
+
+      (local.set $m (i64.and (i64.mul (i64.load32_u (i32.const 160)) (i64.const 4026531839)) (i64.const 0xffffffff)))
+      (local.set $t (i64.add (i64.load32_u (i32.const 160)) (i64.mul (local.get $m) (i64.load32_u (i32.const 64)))))
+      (local.set $c (i64.shr_u (local.get $t) (i64.const 32)))
+      (local.set $j (i32.const 1))
+      (block $done (loop $next
+        (br_if $done (i32.ge_u (local.get $j) (i32.const 8)))
+        (local.set $t (i64.add (i64.add
+          (i64.load32_u (i32.add (i32.const 160) (i32.shl (local.get $j) (i32.const 2))))
+          (i64.mul (local.get $m) (i64.load32_u (i32.add (i32.const 64) (i32.shl (local.get $j) (i32.const 2))))))
+          (local.get $c)))
+        (i64.store32 (i32.add (i32.const 156) (i32.shl (local.get $j) (i32.const 2))) (local.get $t))
+        (local.set $c (i64.shr_u (local.get $t) (i64.const 32)))
+        (local.set $j (i32.add (local.get $j) (i32.const 1)))
+        (br $next)))
+      (local.set $t (i64.add (i64.load32_u (i32.const 192)) (local.get $c)))
+      (i64.store32 (i32.const 188) (local.get $t))
+      (i64.store32 (i32.const 192) (i64.add (i64.load32_u (i32.const 196)) (i64.shr_u (local.get $t) (i64.const 32))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br $outer)))
+    (if (i32.or (i32.ne (i32.load (i32.const 192)) (i32.const 0)) (call $gte (i32.const 160) (i32.const 64)))
+      (then (call $sub (i32.const 160) (i32.const 64) (i32.const 160))))
+    (call $copy (i32.const 160) (local.get $r) (i32.const 8)))
+
+  ;; dst = the long normal form of the element at src
+  (func $to_long (param $src i32) (param $dst i32)
+    (local $short i32)
+    (if (i32.and (i32.load (i32.add (local.get $src) (i32.const 4))) (i32.const 0x80000000))
+      (then
+        (call $copy (i32.add (local.get $src) (i32.const 8)) (local.get $dst) (i32.const 8))
+        (if (i32.and (i32.load (i32.add (local.get $src) (i32.const 4))) (i32.const 0x40000000))
+          (then
+            (call $zero (i32.const 288) (i32.const 8))
+            (i32.store (i32.const 288) (i32.const 1))
+            (call $montmul (local.get $dst) (i32.const 288) (local.get $dst)))))
+      (else
+        (local.set $short (i32.load (local.get $src)))
+        (call $zero (local.get $dst) (i32.const 8))
+        (if (i32.ge_s (local.get $short) (i32.const 0))
+          (then (i32.store (local.get $dst) (local.get $short)))
+          (else
+            (call $zero (i32.const 288) (i32.const 8))
+            (i32.store (i32.const 288) (i32.sub (i32.const 0) (local.get $short)))
+            (call $sub (i32.const 64) (i32.const 288) (local.get $dst)))))))
+  (func (export "getFrLen") (result i32) (i32.const 40))
+  (func (export "getPRawPrime") (result i32) (i32.const 64))
+  (func (export "init") (param $sanity_check i32)
+    (call $zero (i32.const 384) (i32.const 40))
+    (i32.store (i32.const 388) (i32.const 0xc0000000))
+    (call $copy (i32.const 128) (i32.const 392) (i32.const 8))
+    (global.set $inputs (i32.const 0)))
+  (func (export "getSignalOffset32") (param $p_r i32) (param $component i32) (param $h_msb i32) (param $h_lsb i32)
+    (if (i32.and (i32.eq (local.get $h_msb) (i32.const -1352410036)) (i32.eq (local.get $h_lsb) (i32.const -2046694260)))
+      (then (i32.store (local.get $p_r) (i32.const 2)) (return)))
+    (if (i32.and (i32.eq (local.get $h_msb) (i32.const -1352409268)) (i32.eq (local.get $h_lsb) (i32.const -2046692955)))
+      (then (i32.store (local.get $p_r) (i32.const 3)) (return)))
+    (call $error (i32.const 1) (i32.const 0) (local.get $h_msb) (local.get $h_lsb) (i32.const 0) (i32.const 0))
+    (unreachable))
+  (func (export "getSignalSizes32") (param $p_r i32) (param $component i32) (param $h_msb i32) (param $h_lsb i32)
+    (if (i32.or
+          (i32.and (i32.eq (local.get $h_msb) (i32.const -1352410036)) (i32.eq (local.get $h_lsb) (i32.const -2046694260)))
+          (i32.and (i32.eq (local.get $h_msb) (i32.const -1352409268)) (i32.eq (local.get $h_lsb) (i32.const -2046692955))))
+      (then (i32.store (local.get $p_r) (i32.const 352)) (return)))
+    (call $error (i32.const 1) (i32.const 0) (local.get $h_msb) (local.get $h_lsb) (i32.const 0) (i32.const 0))
+    (unreachable))
+  (func (export "setSignal") (param $c_idx i32) (param $component i32) (param $signal i32) (param $p_val i32)
+    (call $copy (local.get $p_val) (i32.add (i32.const 384) (i32.mul (local.get $signal) (i32.const 40))) (i32.const 10))
+    (global.set $inputs (i32.add (global.get $inputs) (i32.const 1)))
+    (if (i32.eq (global.get $inputs) (i32.const 2))
+      (then
+        (call $to_long (i32.const 464) (i32.const 224))
+        (call $to_long (i32.const 504) (i32.const 256))
+        (call $montmul (i32.const 224) (i32.const 96) (i32.const 224))
+        (call $montmul (i32.const 256) (i32.const 96) (i32.const 256))
+        (i32.store (i32.const 428) (i32.const 0xc0000000))
+        (call $montmul (i32.const 224) (i32.const 256) (i32.const 432)))))
+  (func (export "getNVars") (result i32) (i32.const 4))
+  (func (export "getPWitness") (param $i i32) (result i32)
+    (i32.add (i32.const 384) (i32.mul (local.get $i) (i32.const 40))))
+)
@@ -0,0 +1,140 @@
+;; Hand-written witness calculator of `out <== a * b` over bn128, with the interface circom 2 compiles to:
+;; the wasm owns its memory and field elements are exchanged through a shared buffer of 8 words, in normal form.
+;; Signals are one, out, a, b, 8 words each from 384. The buffer is at 320.
+(module
+  (import "runtime" "exceptionHandler" (func $exception (param i32)))
+  (memory (export "memory") 1)
+  (global $inputs (mut i32) (i32.const 0))
+  ;; constants, little endian 32 bit words
+  (data (i32.const 64) "\01\00\00\f0\93\f5\e1\43\91\70\b9\79\48\e8\33\28\5d\58\81\81\b6\45\50\b8\29\a0\31\e1\72\4e\64\30")
+  (data (i32.const 96) "\a7\6d\21\ae\45\e6\b8\1b\e3\59\5c\e3\b1\3a\fe\53\85\80\bb\53\3d\83\49\8c\a5\44\4e\7f\b1\d0\16\02")
+  (data (i32.const 128) "\fb\ff\ff\4f\1c\34\96\ac\29\cd\60\9f\95\76\fc\36\2e\46\79\78\6f\a3\6e\66\2f\df\07\9a\c1\77\0a\0e")
+
+  ;; dst = src, n words
+  (func $copy (param $src i32) (param $dst i32) (param $n i32)
+    (local $i i32)
+    (block $done (loop $next
+      (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+      (i32.store (i32.add (local.get $dst) (i32.shl (local.get $i) (i32.const 2)))
+        (i32.load (i32.add (local.get $src) (i32.shl (local.get $i) (i32.const 2)))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br $next))))
+
+  ;; zeroes n words from dst
+  (func $zero (param $dst i32) (param $n i32)
+    (local $i i32)
+    (block $done (loop $next
+      (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+      (i32.store (i32.add (local.get $dst) (i32.shl (local.get $i) (i32.const 2))) (i32.const 0))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br $next))))
+
+  ;; 1 when a >= b, 8 words
+  (func $gte (param $a i32) (param $b i32) (result i32)
+    (local $i i32) (local $x i32) (local $y i32)
+    (local.set $i (i32.const 7))
+    (block $done (loop $next
+      (br_if $done (i32.lt_s (local.get $i) (i32.const 0)))
+      (local.set $x (i32.load (i32.add (local.get $a) (i32.shl (local.get $i) (i32.const 2)))))
+      (local.set $y (i32.load (i32.add (local.get $b) (i32.shl (local.get $i) (i32.const 2)))))
+      (if (i32.gt_u (local.get $x) (local.get $y)) (then (return (i32.const 1))))
+      (if (i32.lt_u (local.get $x) (local.get $y)) (then (return (i32.const 0))))
+      (local.set $i (i32.sub (local.get $i) (i32.const 1)))
+      (br $next)))
+    (i32.const 1))
+
+  ;; r = a - b, 8 words
+  (func $sub (param $a i32) (param $b i32) (param $r i32)
+    (local $i i32) (local $t i64) (local $borrow i64)
+    (block $done (loop $next
+      (br_if $done (i32.ge_u (local.get $i) (i32.const 8)))
+      (local.set $t (i64.sub (i64.sub
+        (i64.load32_u (i32.add (local.get $a) (i32.shl (local.get $i) (i32.const 2))))
+        (i64.load32_u (i32.add (local.get $b) (i32.shl (local.get $i) (i32.const 2)))))
+        (local.get $borrow)))
+      (i64.store32 (i32.add (local.get $r) (i32.shl (local.get $i) (i32.const 2))) (local.get $t))
+      (local.set $borrow (i64.extend_i32_u (i64.lt_s (local.get $t) (i64.const 0))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br $next))))
+
+  ;; r = x * y / 2^256 mod p, montgomery multiplication over 8 words, the scratch t is 10 words at 160
+  (func $montmul (param $x i32) (param $y i32) (param $r i32)
+    (local $i i32) (local $j i32) (local $c i64) (local $m i64) (local $t i64) (local $yi i64)
+    (call $zero (i32.const 160) (i32.const 10))
+    (block $outer_done (loop $outer
+      (br_if $outer_done (i32.ge_u (local.get $i) (i32.const 8)))
+      (local.set $yi (i64.load32_u (i32.add (local.get $y) (i32.shl (local.get $i) (i32.const 2)))))
+      (local.set $c (i64.const 0))
+      (local.set $j (i32.const 0))
+      (block $done (loop $next
+        (br_if $done (i32.ge_u (local.get $j) (i32.const 8)))
+        (local.set $t (i64.add (i64.add
+          (i64.load32_u (i32.add (i32.const 160) (i32.shl (local.get $j) (i32.const 2))))
+          (i64.mul (i64.load32_u (i32.add (local.get $x) (i32.shl (local.get $j) (i32.const 2)))) (local.get $yi)))
+          (local.get $c)))
+        (i64.store32 (i32.add (i32.const 160) (i32.shl (local.get $j) (i32.const 2))) (local.get $t))
+        (local.set $c (i64.shr_u (local.get $t) (i64.const 32)))
+        (local.set $j (i32.add (local.get $j) (i32.const 1)))
+        (br $next)))
+      (local.set $t (i64.add (i64.load32_u (i32.const 192)) (local.get $c)))
+      (i64.store32 (i32.const 192) (local.get $t))
+      (i64.store32 (i32.const 196) (i64.shr_u (local.get $t) (i64.const 32)))
+
+      (local.set $m (i64.and (i64.mul (i64.load32_u (i32.const 160)) (i64.const 4026531839)) (i64.const 0xffffffff)))
+      (local.set $t (i64.add (i64.load32_u (i32.const 160)) (i64.mul (local.get $m) (i64.load32_u (i32.const 64)))))
+      (local.set $c (i64.shr_u (local.get $t) (i64.const 32)))
+      (local.set $j (i32.const 1))
+      (block $done (loop $next
+        (br_if $done (i32.ge_u (local.get $j) (i32.const 8)))
+        (local.set $t (i64.add (i64.add
+          (i64.load32_u (i32.add (i32.const 160) (i32.shl (local.get $j) (i32.const 2))))
+          (i64.mul (local.get $m) (i64.load32_u (i32.add (i32.const 64) (i32.shl (local.get $j) (i32.const 2))))))
+          (local.get $c)))
+        (i64.store32 (i32.add (i32.const 156) (i32.shl (local.get $j) (i32.const 2))) (local.get $t))
+        (local.set $c (i64.shr_u (local.get $t) (i64.const 32)))
+        (local.set $j (i32.add (local.get $j) (i32.const 1)))
+        (br $next)))
+      (local.set $t (i64.add (i64.load32_u (i32.const 192)) (local.get $c)))
+      (i64.store32 (i32.const 188) (local.get $t))
+      (i64.store32 (i32.const 192) (i64.add (i64.load32_u (i32.const 196)) (i64.shr_u (local.get $t) (i64.const 32))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br $outer)))
+    (if (i32.or (i32.ne (i32.load (i32.const 192)) (i32.const 0)) (call $gte (i32.const 160) (i32.const 64)))
+      (then (call $sub (i32.const 160) (i32.const 64) (i32.const 160))))
+    (call $copy (i32.const 160) (local.get $r) (i32.const 8)))
+
+  (func (export "getVersion") (result i32) (i32.const 2))
+  (func (export "getFieldNumLen32") (result i32) (i32.const 8))
+  (func (export "getRawPrime") (call $copy (i32.const 64) (i32.const 320) (i32.const 8)))
+  (func (export "readSharedRWMemory") (param $i i32) (result i32)
+    (i32.load (i32.add (i32.const 320) (i32.shl (local.get $i) (i32.const 2)))))
+  (func (export "writeSharedRWMemory") (param $i i32) (param $v i32)
+    (i32.store (i32.add (i32.const 320) (i32.shl (local.get $i) (i32.const 2))) (local.get $v)))
+  (func (export "init") (param $sanity_check i32)
+    (call $zero (i32.const 384) (i32.const 32))
+    (i32.store (i32.const 384) (i32.const 1))
+    (global.set $inputs (i32.const 0)))
+  (func $signal (param $h_msb i32) (param $h_lsb i32) (result i32)
+    (if (i32.and (i32.eq (local.get $h_msb) (i32.const -1352410036)) (i32.eq (local.get $h_lsb) (i32.const -2046694260)))
+      (then (return (i32.const 2))))
+    (if (i32.and (i32.eq (local.get $h_msb) (i32.const -1352409268)) (i32.eq (local.get $h_lsb) (i32.const -2046692955)))
+      (then (return (i32.const 3))))
+    (i32.const -1))
+  (func (export "getInputSignalSize") (param $h_msb i32) (param $h_lsb i32) (result i32)
+    (if (result i32) (i32.lt_s (call $signal (local.get $h_msb) (local.get $h_lsb)) (i32.const 0))
+      (then (i32.const -1))
+      (else (i32.const 1))))
+  (func (export "setInputSignal") (param $h_msb i32) (param $h_lsb i32) (param $pos i32)
+    (local $signal i32)
+    (local.set $signal (call $signal (local.get $h_msb) (local.get $h_lsb)))
+    (if (i32.lt_s (local.get $signal) (i32.const 0)) (then (call $exception (i32.const 1)) (unreachable)))
+    (call $copy (i32.const 320) (i32.add (i32.const 384) (i32.shl (local.get $signal) (i32.const 5))) (i32.const 8))
+    (global.set $inputs (i32.add (global.get $inputs) (i32.const 1)))
+    (if (i32.eq (global.get $inputs) (i32.const 2))
+      (then
+        (call $montmul (i32.const 448) (i32.const 480) (i32.const 224))
+        (call $montmul (i32.const 224) (i32.const 96) (i32.const 416)))))
+  (func (export "getWitnessSize") (result i32) (i32.const 4))
+  (func (export "getWitness") (param $i i32)
+    (call $copy (i32.add (i32.const 384) (i32.shl (local.get $i) (i32.const 5))) (i32.const 320) (i32.const 8)))
+)
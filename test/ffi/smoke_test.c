@@ -0,0 +1,71 @@
+// Proves and verifies a witness through the C API of libplonkit.
+// usage: smoke_test <circuit.r1cs> <setup.key> <witness.wtns>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "plonkit.h"
+
+static uint8_t *read_file(const char *filename, size_t *len) {
+  FILE *file = fopen(filename, "rb");
+  if (file == NULL) {
+    perror(filename);
+    exit(1);
+  }
+  fseek(file, 0, SEEK_END);
+  *len = (size_t)ftell(file);
+  fseek(file, 0, SEEK_SET);
+  uint8_t *data = malloc(*len);
+  if (fread(data, 1, *len, file) != *len) {
+    perror(filename);
+    exit(1);
+  }
+  fclose(file);
+  return data;
+}
+
+static void check(int ok, const char *step, char *error) {
+  if (!ok) {
+    fprintf(stderr, "%s failed: %s\n", step, error != NULL ? error : "unexpected result");
+    plonkit_error_free(error);
+    exit(1);
+  }
+  printf("%s ok\n", step);
+}
+
+int main(int argc, char **argv) {
+  if (argc != 4) {
+    fprintf(stderr, "usage: %s <circuit.r1cs> <setup.key> <witness.wtns>\n", argv[0]);
+    return 2;
+  }
+  size_t circuit_len, srs_len, witness_len;
+  uint8_t *circuit = read_file(argv[1], &circuit_len);
+  uint8_t *srs = read_file(argv[2], &srs_len);
+  uint8_t *witness = read_file(argv[3], &witness_len);
+  char *error = NULL;
+
+  PlonkitProver *prover = plonkit_prover_new(circuit, circuit_len, srs, srs_len, NULL, 0, NULL, 0, &error);
+  check(prover != NULL, "plonkit_prover_new", error);
+
+  PlonkitBuffer proof;
+  check(plonkit_prove(prover, witness, witness_len, &proof, &error) == 0, "plonkit_prove", error);
+
+  PlonkitBuffer vk;
+  check(plonkit_prover_verification_key(prover, &vk, &error) == 0, "plonkit_prover_verification_key", error);
+
+  PlonkitVerifier *verifier = plonkit_verifier_new(vk.data, vk.len, &error);
+  check(verifier != NULL, "plonkit_verifier_new", error);
+  check(plonkit_verify(verifier, proof.data, proof.len, &error) == 1, "plonkit_verify", error);
+
+  // a truncated proof is an error, not a crash
+  check(plonkit_verify(verifier, proof.data, proof.len / 2, &error) == -1 && error != NULL, "plonkit_verify truncated", NULL);
+  plonkit_error_free(error);
+
+  plonkit_verifier_free(verifier);
+  plonkit_buffer_free(vk);
+  plonkit_buffer_free(proof);
+  plonkit_prover_free(prover);
+  free(circuit);
+  free(srs);
+  free(witness);
+  return 0;
+}
@@ -0,0 +1,27 @@
+// Proves and verifies a witness through the Node.js addon, built with `--features node`.
+// usage: node smoke_test.mjs <plonkit.node> <circuit.r1cs> <setup.key> <witness.wtns>
+import * as fs from 'fs';
+import * as path from 'path';
+import { createRequire } from 'module';
+const require = createRequire(import.meta.url);
+
+const [addon, circuit, srs, witness] = process.argv.slice(2);
+const { Prover } = require(path.resolve(addon));
+
+async function main() {
+  const prover = new Prover(circuit, srs);
+  const { proof, inputs } = await prover.prove(fs.readFileSync(witness));
+  if (!prover.verify(proof, inputs)) {
+    throw new Error('the proof does not verify');
+  }
+  const wrongInputs = [(BigInt(inputs[0]) + 1n).toString(), ...inputs.slice(1)];
+  if (prover.verify(proof, wrongInputs)) {
+    throw new Error('the proof verifies with wrong inputs');
+  }
+  console.log('ok');
+}
+
+main().catch((e) => {
+  console.error(e);
+  process.exit(1);
+});
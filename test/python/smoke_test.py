@@ -0,0 +1,15 @@
+# Proves and verifies a witness through the Python module, built with `--features python`.
+# usage: PYTHONPATH=<dir of plonkit.so> python3 smoke_test.py <circuit.r1cs> <setup.key> <witness.wtns> <vk.bin>
+import sys
+
+import plonkit
+
+circuit, srs, witness, vk = sys.argv[1:]
+assert plonkit.analyse(circuit)["num_gates"] > 0
+report = plonkit.check_witness(circuit, witness)
+assert report["is_valid"], report
+
+result = plonkit.prove(circuit, witness, srs)
+assert len(result["serialized_proof"]) > 0
+assert plonkit.verify(vk, result["proof"]), "the proof does not verify"
+print("ok")